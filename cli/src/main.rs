@@ -5,26 +5,65 @@ use fastrand::Rng;
 use git_version::git_version;
 use itertools::Itertools;
 use mimalloc::MiMalloc;
-use odd_engine::{Card, Game, GameOutcome, GameState, Outcome, Player, HOLE_CARDS_PER_PLAYER};
-use structopt::StructOpt;
+use odd_engine::{
+    texture, Card, Effect, Game, GameOutcome, GameState, Method, NextCards, Nuts, Odds, Outcome,
+    Player, Pots, PreflopTable, Range, Stakes, StoppingRule, Variant, Z_95,
+};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, IsTerminal};
+use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use structopt::{clap, StructOpt};
 
 #[global_allocator]
 static ALLOCATOR: MiMalloc = MiMalloc;
 
+/// Set by the first Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let opt = Opt::from_args();
-    let players = opt
-        .hole_cards
-        .chunks_exact(HOLE_CARDS_PER_PLAYER)
-        .map(|x| x.try_into().unwrap())
-        .collect_vec();
+    if !matches!(opt.command, Some(Command::PreflopTable(_))) {
+        ctrlc::set_handler(|| {
+            if INTERRUPTED.swap(true, Ordering::Relaxed) {
+                process::exit(130);
+            }
+        })?;
+    }
+    match opt.command {
+        Some(Command::NextCard(ref game)) => next_card(game),
+        Some(Command::Outs(ref game)) => outs(game),
+        Some(Command::Nuts(ref opt)) => nuts(opt),
+        Some(Command::PreflopTable(ref opt)) => preflop_table(opt),
+        None => odds(&opt),
+    }
+}
+
+fn deal(opt: &GameOpt) -> Vec<Range> {
+    let players = players(&opt.players, opt.variant.hole_cards()).unwrap_or_else(|message| {
+        clap::Error::with_description(&message, clap::ErrorKind::WrongNumberOfValues).exit()
+    });
     for (i, player) in players.iter().enumerate() {
-        print!("player {:2} was dealt: ", i + 1);
-        for card in player {
-            print!("{} ", card);
+        match player.fixed() {
+            Some(hole_cards) => {
+                print!("player {:2} was dealt: ", i + 1);
+                for card in hole_cards {
+                    print!("{} ", card);
+                }
+            }
+            None => print!("player {:2} has one of {} combos", i + 1, player.weight()),
         }
         println!();
     }
+    for (i, range) in opt.opponent_ranges.iter().enumerate() {
+        println!("opponent {:2} has one of {} combos", i + 1, range.weight());
+    }
 
     let flop = opt.board.get(..3);
     let turn = opt.board.get(3..4);
@@ -41,19 +80,79 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         println!();
     }
+    if let Some(texture) = texture(opt.variant, &opt.board) {
+        println!(
+            "texture: {}; possible straights {}, flushes {}",
+            texture, texture.straights, texture.flushes
+        );
+    }
+    players
+}
 
-    let rng = Rng::with_seed(opt.seed);
-    let rng = RngAdapter(rng);
+fn odds(opt: &Opt) -> Result<(), Box<dyn std::error::Error>> {
+    let players = deal(&opt.game);
+    let opt_game = &opt.game;
+    let rng = RngAdapter(Rng::with_seed(opt_game.seed));
     let n_players = players.len();
-    let game = Game::new(players, opt.board, opt.opponents, opt.folded);
+    // the streets still to come, including the river
+    let streets = ["flop", "turn", "river"]
+        .into_iter()
+        .skip(opt_game.board.len().saturating_sub(2))
+        .collect::<Vec<_>>();
+    let rule = opt_game.stopping_rule();
+    let stakes = opt
+        .pot
+        .map(|pot| Stakes::new(pot, opt.to_call.unwrap_or(0.0), opt.stack))
+        .transpose()?;
+    let mut game = opt_game
+        .game(players.clone())
+        .with_streets(opt.streets)
+        .with_contributions(opt.contributions.clone());
+    if let Some(path) = &opt.preflop_table {
+        let table = PreflopTable::read(BufReader::new(File::open(path)?))?;
+        game = game.with_preflop_table(Arc::new(table));
+    }
+    let outcome = game.play_observed(rng, rule, progress_bar());
+    if io::stderr().is_terminal() {
+        // erase the progress bar
+        eprint!("\r\x1b[2K");
+    }
     let GameOutcome {
         state,
         cards_remaining,
-    } = game.play(rng, opt.permutations)?;
+        method,
+        live_combos,
+        samples,
+        stop_reason,
+        pots,
+    } = outcome?;
 
     println!();
 
     println!("{} cards remain.", cards_remaining);
+    for (i, (player, live)) in zip(&players, live_combos).enumerate() {
+        if player.fixed().is_none() {
+            println!("player {:2} has {} live combos.", i + 1, live);
+        }
+    }
+
+    for (i, (player, draws)) in zip(&players, game.draws()?.unwrap_or_default()).enumerate() {
+        let draws = draws.percentages().collect::<Vec<_>>();
+        if draws.is_empty() {
+            continue;
+        }
+        print!("player {:2} draws: ", i + 1);
+        println!(
+            "{}",
+            draws
+                .into_iter()
+                .map(|(draw, percent)| match player.fixed() {
+                    Some(_) => draw.to_string(),
+                    None => format!("{} {:.2}%", draw, percent),
+                })
+                .join(", ")
+        );
+    }
 
     println!();
 
@@ -61,38 +160,343 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         GameState::GameOver(outcomes) => {
             for (i, outcome) in outcomes.into_iter().enumerate() {
                 print!("player {:2} has {} ", i + 1, outcome.hand);
-                match outcome.outcome {
-                    Outcome::Win => print!("(winner)"),
-                    Outcome::Tie => print!("(tie)"),
-                    Outcome::Loss => print!("(lost)"),
+                print_outcome(outcome.outcome);
+                if let Some((low_outcome, low)) = outcome.low {
+                    match low {
+                        Some(low) => {
+                            print!(", {} ", low);
+                            print_outcome(low_outcome);
+                        }
+                        None => print!(", no low"),
+                    }
+                    print!(", {:.0}% of the pot", 100.0 * outcome.share);
                 }
                 println!();
             }
         }
         GameState::Undecided(all_odds) => {
-            for odds in all_odds.merge_unknown_players(n_players).into_iter() {
+            match method {
+                Method::Exhaustive => println!("exact odds (all scenarios enumerated):"),
+                Method::MonteCarlo => println!(
+                    "estimated odds (random sample of scenarios, ± 95% confidence interval):"
+                ),
+                Method::Lookup => println!(
+                    "estimated odds (looked up in the preflop table, {} scenarios per hand):",
+                    samples
+                ),
+            }
+            if let Some(reason) = stop_reason {
+                println!("sampled {} scenarios, then stopped: {}", samples, reason);
+            }
+            // exact odds have no sampling error to speak of
+            let margin = |standard_error: f64| match method {
+                Method::Exhaustive | Method::Lookup => String::new(),
+                Method::MonteCarlo => format!(" ±{:.2}", Z_95 * standard_error),
+            };
+            for (i, odds) in all_odds
+                .merge_unknown_players(n_players)
+                .into_iter()
+                .enumerate()
+            {
                 match odds.who {
                     Player::Single(id) => print!("   player {:2}: ", id),
                     Player::Multiple(count) => print!("{:2} opponents: ", count),
                 }
                 println!(
-                    "win {:5.2}%, tie {:5.2}%, loss {:5.2}%",
+                    "win {:5.2}%{}, tie {:5.2}%{}, loss {:5.2}%{}, equity {:5.2}%{}",
                     odds.win_percent(),
+                    margin(odds.win_standard_error()),
                     odds.tie_percent(),
+                    margin(odds.tie_standard_error()),
                     odds.loss_percent(),
+                    margin(odds.loss_standard_error()),
+                    odds.equity_percent(),
+                    margin(odds.equity_standard_error()),
                 );
+                if let Some(stakes) = stakes.filter(|_| i == 0) {
+                    let ev = odds.ev(stakes);
+                    println!(
+                        "{:15}call EV {:+.2}, pot odds {:5.2}%, a bluff this size needs {:5.2}% folds",
+                        "", ev.call, ev.required_equity, ev.bluff_fold_frequency,
+                    );
+                }
+                if opt_game.variant.is_hi_lo() {
+                    println!(
+                        "{:15}scoop {:5.2}%, low {:5.2}%, split {:5.2}%, quartered {:5.2}%",
+                        "",
+                        odds.scoop_percent(),
+                        odds.low_win_percent(),
+                        odds.split_percent(),
+                        odds.quartered_percent(),
+                    );
+                }
+                if opt.streets && !opt_game.variant.streets().is_empty() {
+                    let equities = odds.street_equity_percent().chain([odds.equity_percent()]);
+                    println!(
+                        "{:15}{}",
+                        "",
+                        zip(&streets, equities)
+                            .map(|(street, equity)| format!("{} {:5.2}%", street, equity))
+                            .join(", ")
+                    );
+                }
                 if !opt.distribution {
-                    for (hand_type, percent) in odds.distribution() {
-                        println!("{:20}: {:5.2}%", *hand_type, percent);
+                    for (hand_type, percent, standard_error) in odds.distribution() {
+                        println!(
+                            "{:20}: {:5.2}%{}",
+                            *hand_type,
+                            percent,
+                            margin(standard_error)
+                        );
                     }
                     println!();
                 }
             }
         }
     }
+    if let Some(pots) = pots {
+        print_pots(&pots, n_players);
+    }
+    Ok(())
+}
+
+fn print_pots(pots: &Pots, n_players: usize) {
+    let label = |i: usize| match i.checked_sub(n_players) {
+        None => format!("player {}", i + 1),
+        Some(opponent) => format!("opponent {}", opponent + 1),
+    };
+    for (k, pot) in pots.pots.iter().enumerate() {
+        let name = match k {
+            0 => "main pot".to_string(),
+            k => format!("side pot {}", k),
+        };
+        println!(
+            "{:10} {:8.2}: {}",
+            name,
+            pot.amount,
+            pot.eligible
+                .iter()
+                .map(|i| format!("{} {:5.2}%", label(*i), pots.equity[*i][k]))
+                .join(", ")
+        );
+    }
+    println!(
+        "{:10} {:8}: {}",
+        "chip EV",
+        "",
+        pots.chip_ev
+            .iter()
+            .enumerate()
+            .map(|(i, ev)| format!("{} {:+.2}", label(i), ev))
+            .join(", ")
+    );
+}
+
+fn next_card(opt: &GameOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let players = deal(opt);
+    let rng = RngAdapter(Rng::with_seed(opt.seed));
+    let NextCards { equity, cards } =
+        opt.game(players.clone())
+            .next_cards_observed(rng, opt.stopping_rule(), |_, _, _| interrupted())?;
+
+    println!();
+
+    let mut labels = (1..=players.len())
+        .map(|i| format!("player {}", i))
+        .collect::<Vec<_>>();
+    if opt.opponents + opt.opponent_ranges.len() > 0 {
+        labels.push("opponents".to_string());
+    }
+    print!("{:10}", "");
+    for label in &labels {
+        print!("{:>12}", label);
+    }
+    println!();
+    print!("{:10}", "now");
+    for equity in &equity {
+        print!("{:11.2}%", equity);
+    }
+    println!();
+    for next in &cards {
+        print!("{}{:7}", next.card, "");
+        for equity in &next.equity {
+            print!("{:11.2}%", equity);
+        }
+        println!();
+    }
+
+    println!();
+
+    for (effect, name) in [
+        (Effect::ImprovesHero, "improves player 1"),
+        (Effect::ImprovesVillain, "improves villain"),
+        (Effect::Neutral, "neutral"),
+    ] {
+        let cards = cards.iter().filter(|next| next.effect == effect);
+        println!("{:18}: {}", name, cards.map(|next| next.card).join(" "));
+    }
+    Ok(())
+}
+
+fn outs(opt: &GameOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let outs = opt.game(deal(opt)).outs()?;
+
+    println!();
+
+    let clean = outs.iter().filter(|out| out.clean).count();
+    println!("player 1 has {} outs, {} of them clean:", outs.len(), clean);
+    for (hand_type, outs) in &outs.iter().group_by(|out| out.hand_type) {
+        print!("{:20}:", hand_type);
+        for out in outs {
+            print!(" {}{}", out.card, if out.clean { "" } else { "*" });
+        }
+        println!();
+    }
+    if clean < outs.len() {
+        println!("* also improves an opponent's hand");
+    }
+    Ok(())
+}
+
+fn nuts(opt: &NutsOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let Nuts { top, strength } = opt
+        .game
+        .game(deal(&opt.game))
+        .nuts_cancellable(opt.top, || INTERRUPTED.load(Ordering::Relaxed))?;
+
+    println!();
+
+    for holding in top {
+        print!("{:3} ", holding.rank);
+        for card in &holding.hole_cards {
+            print!("{} ", card);
+        }
+        println!("{}", holding.hand);
+    }
+    println!();
+    for (i, strength) in strength.into_iter().enumerate() {
+        if let Some(strength) = strength {
+            println!("player {:2} beats {:5.2}% of holdings", i + 1, strength);
+        }
+    }
+    Ok(())
+}
+
+fn preflop_table(opt: &PreflopTableOpt) -> Result<(), Box<dyn std::error::Error>> {
+    let rng = RngAdapter(Rng::with_seed(opt.seed));
+    let terminal = io::stderr().is_terminal();
+    let table = PreflopTable::generate(opt.samples.get(), &rng, |done, total| {
+        if terminal {
+            let done = done as f64 / total as f64;
+            eprint!(
+                "\r[{:40}] {:3.0}%",
+                "#".repeat((done * 40.0) as usize),
+                100.0 * done
+            );
+        }
+    });
+    if terminal {
+        // erase the progress bar
+        eprint!("\r\x1b[2K");
+    }
+    table.write(BufWriter::new(File::create(&opt.output)?))?;
+    println!("wrote the preflop table to {}", opt.output.display());
     Ok(())
 }
 
+fn interrupted() -> ControlFlow<()> {
+    match INTERRUPTED.load(Ordering::Relaxed) {
+        true => ControlFlow::Break(()),
+        false => ControlFlow::Continue(()),
+    }
+}
+
+fn progress_bar() -> impl FnMut(&Odds, usize, usize) -> ControlFlow<()> {
+    let terminal = io::stderr().is_terminal();
+    move |odds, scenarios, total| {
+        if let Some(hero) = odds.iter().next().filter(|_| terminal) {
+            let done = scenarios as f64 / total as f64;
+            eprint!(
+                "\r[{:40}] {:3.0}% equity {:5.2}%",
+                "#".repeat((done * 40.0) as usize),
+                100.0 * done,
+                hero.equity_percent()
+            );
+        }
+        interrupted()
+    }
+}
+
+fn print_outcome(outcome: Outcome) {
+    match outcome {
+        Outcome::Win => print!("(winner)"),
+        Outcome::Tie => print!("(tie)"),
+        Outcome::Loss => print!("(lost)"),
+    }
+}
+
+enum Holding {
+    Card(Card),
+    Range(Range),
+}
+
+impl FromStr for Holding {
+    type Err = odd_engine::RangeParseError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.parse() {
+            Ok(card) => Ok(Holding::Card(card)),
+            Err(_) => Ok(Holding::Range(string.parse()?)),
+        }
+    }
+}
+
+fn players(holdings: &[Holding], hole_cards: usize) -> Result<Vec<Range>, String> {
+    let mut players = vec![];
+    let mut holdings = holdings.iter().peekable();
+    while let Some(holding) = holdings.next() {
+        players.push(match holding {
+            Holding::Card(card) => {
+                let mut cards = vec![*card];
+                while cards.len() < hole_cards {
+                    match holdings.peek() {
+                        Some(Holding::Card(card)) => cards.push(*card),
+                        _ => {
+                            return Err(format!(
+                                "expected {} hole cards starting at {}",
+                                hole_cards, cards[0]
+                            ))
+                        }
+                    }
+                    holdings.next();
+                }
+                Range::from(cards)
+            }
+            Holding::Range(range) => range.clone(),
+        });
+    }
+    Ok(players)
+}
+
+impl GameOpt {
+    fn game(&self, players: Vec<Range>) -> Game {
+        Game::new(players, self.board.clone(), self.opponents, self.folded)
+            .with_variant(self.variant)
+            .with_opponent_ranges(self.opponent_ranges.clone())
+    }
+
+    fn stopping_rule(&self) -> StoppingRule {
+        let mut rule = StoppingRule::samples(self.permutations.get());
+        if let Some(standard_error) = self.target_error {
+            rule = rule.with_target_standard_error(standard_error);
+        }
+        if let Some(millis) = self.time_limit {
+            rule = rule.with_time_limit(Duration::from_millis(millis));
+        }
+        rule
+    }
+}
+
 struct RngAdapter(Rng);
 
 impl odd_engine::Rng<usize> for RngAdapter {
@@ -100,20 +504,109 @@ impl odd_engine::Rng<usize> for RngAdapter {
         self.0.usize(range)
     }
 }
+
+impl odd_engine::SplitRng for RngAdapter {
+    fn stream(&self, index: u64) -> Self {
+        RngAdapter(Rng::with_seed(odd_engine::split_seed(
+            self.0.get_seed(),
+            index,
+        )))
+    }
+}
 const VERSION: &str = git_version!();
 
 #[derive(StructOpt)]
-#[structopt(name = "odd", version = VERSION)]
-/// Texas hold'em poker odds simulator
+#[structopt(name = "odd", version = VERSION, setting = clap::AppSettings::SubcommandsNegateReqs)]
+/// Texas hold'em, Omaha and stud poker odds simulator
 ///
 /// When all the players' hole cards and all five community cards are known, odd detects what hand
 /// each player holds and determines the winners. Otherwise, odd estimates the odds of winning for
 /// each player by generating a configurable number of random deck shuffles to simulate a range of
 /// possible scenarios.
 struct Opt {
-    /// Pairs of hole cards for each (known) player; e.g. As Kd 5h Tc
-    #[structopt(required = true, number_of_values = 2, multiple = true)]
-    hole_cards: Vec<Card>,
+    #[structopt(flatten)]
+    game: GameOpt,
+
+    /// Whether to include each player's equity had the hand been shown down after each street
+    #[structopt(long)]
+    streets: bool,
+
+    /// Whether to include hand distribution in the output
+    #[structopt(short, long)]
+    distribution: bool,
+
+    /// Size of the pot, including any bet player 1 faces
+    #[structopt(long)]
+    pot: Option<f64>,
+
+    /// Amount player 1 must put in to call, if anything
+    #[structopt(long, requires = "pot")]
+    to_call: Option<f64>,
+
+    /// Smaller of player 1's stack and the bettor's
+    #[structopt(long, requires = "pot")]
+    stack: Option<f64>,
+
+    /// Chips each player still in has put in the pot, known players first, then opponents with
+    /// ranges and then any other opponents
+    #[structopt(short, long, min_values = 1)]
+    contributions: Vec<f64>,
+
+    /// Preflop table to look up preflop hold'em odds in
+    #[structopt(long, parse(from_os_str))]
+    preflop_table: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Lists each card that could come next and each player's equity once it does
+    NextCard(GameOpt),
+    /// Lists the cards that would make player 1 the winner if dealt next
+    Outs(GameOpt),
+    /// Lists the strongest holdings on the board
+    Nuts(NutsOpt),
+    /// Writes the preflop odds of every hold'em starting hand to a file for --preflop-table
+    PreflopTable(PreflopTableOpt),
+}
+
+#[derive(StructOpt)]
+struct PreflopTableOpt {
+    /// File to write the table to
+    #[structopt(parse(from_os_str))]
+    output: PathBuf,
+
+    /// Number of deck permutations to generate for each entry in the table
+    #[structopt(short = "n", long, default_value = "100000")]
+    samples: NonZeroUsize,
+
+    /// RNG seed used for generating permutations of the deck
+    #[structopt(short, long, default_value = "1")]
+    seed: u64,
+}
+
+#[derive(StructOpt)]
+struct NutsOpt {
+    #[structopt(flatten)]
+    game: GameOpt,
+
+    /// Number of holdings to list
+    #[structopt(short = "n", long, default_value = "10")]
+    top: usize,
+}
+
+#[derive(StructOpt)]
+struct GameOpt {
+    /// Hole cards or ranges for each (known) player; e.g. As Kd 5h Tc or QQ+,AKs
+    #[structopt(required = true, multiple = true)]
+    players: Vec<Holding>,
+
+    /// Game being played: holdem, omaha (plo), omaha5 (plo5), omaha8 (plo8), omaha5-8 (plo5-8),
+    /// stud8 or shortdeck (6+)
+    #[structopt(short, long, default_value = "holdem")]
+    variant: Variant,
 
     /// Community cards comprising the flop, turn, and river; e.g. 2s 3h 4c 5d 6s
     #[structopt(short, long, min_values = 3, max_values = 5)]
@@ -123,6 +616,10 @@ struct Opt {
     #[structopt(short, long, default_value = "0")]
     opponents: usize,
 
+    /// Range of another additional player, once per player; e.g. -r QQ+,AKs -r 22+
+    #[structopt(short = "r", long = "opponent-range", number_of_values = 1)]
+    opponent_ranges: Vec<Range>,
+
     /// Number of additional players with unknown hole cards who have folded
     #[structopt(short, long, default_value = "0")]
     folded: usize,
@@ -131,11 +628,15 @@ struct Opt {
     #[structopt(short, long, default_value = "1")]
     seed: u64,
 
-    /// Number of deck permutations to generate
+    /// Maximum number of deck permutations to generate
     #[structopt(short, long, default_value = "1000000")]
-    permutations: usize,
+    permutations: NonZeroUsize,
 
-    /// Whether to include hand distribution in the output
+    /// Standard error of player 1's equity, in percentage points, at which to stop
+    #[structopt(short = "e", long)]
+    target_error: Option<f64>,
+
+    /// Stop generating permutations after this many milliseconds
     #[structopt(short, long)]
-    distribution: bool,
+    time_limit: Option<u64>,
 }
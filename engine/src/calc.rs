@@ -1,74 +1,534 @@
-use crate::card::{Cards, Players, HOLE_CARDS_PER_PLAYER};
+use crate::card::{mask, Card, Cards, HoleCards, Players};
 use crate::deck::Deck;
-use crate::floyd::permutations;
-use crate::floyd::Rng;
-use crate::hand::{hands, Hand, HandType};
+use crate::eval::LowRank;
+use crate::floyd::{Rng, SplitRng};
+use crate::hand::{Hand, HandType};
+use crate::pots::SidePot;
+use crate::range::Range;
+use crate::suits::symmetries;
+use crate::variant::Variant;
 use itertools::Itertools;
 use rayon::prelude::*;
 use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::iter::zip;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
 use Outcome::*;
 
-pub const BOARD_LENGTH: usize = 5;
+/// Most scenarios `Game::play` enumerates rather than samples.
+pub const EXHAUSTIVE_THRESHOLD: u64 = 2_000_000;
 
-pub fn odds(
-    opponents: usize,
-    n_folded: usize,
-    players: &Players,
-    board: &Cards,
-    deck: Deck,
-    desired_samples: usize,
-    rng: impl Rng<usize> + Send,
-) -> Odds
-where
-{
-    let unknown_hole_cards = HOLE_CARDS_PER_PLAYER * (opponents + n_folded);
-    let unknown_board_cards = BOARD_LENGTH - board.len();
+/// The 97.5th percentile of the standard normal distribution.
+pub const Z_95: f64 = 1.959_964;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Spot<'a> {
+    pub variant: Variant,
+    pub players: &'a [Range],
+    pub board: &'a Cards,
+    pub opponents: usize,
+    pub n_folded: usize,
+    /// Whether to also record equity after each street to come.
+    pub streets: bool,
+    /// Side pots to also record equity in, if any.
+    pub pots: &'a [SidePot],
+}
+
+impl Spot<'_> {
+    fn streets(&self) -> Vec<usize> {
+        let dealt = self.board.len();
+        let streets = self.variant.streets().iter();
+        streets
+            .filter(|street| self.streets && **street > dealt)
+            .map(|street| street - dealt)
+            .filter(|unknown| *unknown < self.variant.board_length() - dealt)
+            .collect()
+    }
+}
+
+/// Number of scenarios `odds` deals from each of its random streams.
+const CHUNK_SIZE: usize = 4096;
+
+pub fn odds<R: SplitRng + Sync>(spot: Spot, deck: Deck, desired_samples: usize, rng: &R) -> Odds {
+    let Spot {
+        variant,
+        players,
+        board,
+        opponents,
+        n_folded,
+        pots,
+        ..
+    } = spot;
+    let streets = spot.streets();
+    let hole_cards = variant.hole_cards();
+    let unknown_hole_cards = hole_cards * (opponents + n_folded);
+    let unknown_board_cards = variant.board_length() - board.len();
     let unknown_cards = unknown_hole_cards + unknown_board_cards;
     let new_odds = || Odds::new(opponents + players.len());
-    permutations(unknown_cards, deck.consume().collect_vec(), rng)
-        .take(desired_samples)
-        .collect_vec()
+    let board_mask = mask(board);
+    let deal = |rng: &mut R| {
+        let holes = deal_ranges(players, rng);
+        // in stud, known players may still be missing cards from later streets
+        let missing = holes
+            .iter()
+            .map(|hole| hole_cards - hole.len())
+            .sum::<usize>();
+        let mut deck = deck.difference(holes.iter().copied().flatten().collect());
+        let scenario = deck.deal(missing + unknown_cards, rng);
+        let (missing, scenario) = scenario.split_at(missing);
+        let (extra_hole, extra_board) = scenario.split_at(unknown_hole_cards);
+        let extra_players = extra_hole.chunks_exact(hole_cards).skip(n_folded);
+        let mut missing = missing.iter();
+        let players = holes
+            .iter()
+            .map(|hole| {
+                let dealt = missing.by_ref().take(hole_cards - hole.len());
+                dealt.fold(mask(hole), |cards, card| cards | card.mask())
+            })
+            .chain(extra_players.map(mask))
+            .collect_vec();
+        let streets = streets
+            .iter()
+            .map(|n| board_mask | mask(&extra_board[..*n]))
+            .collect_vec();
+        (players, board_mask | mask(extra_board), streets)
+    };
+    // each chunk of scenarios is dealt from its own stream as it's evaluated, and the chunks' odds
+    // are merged in order, so that the result doesn't depend on the number of threads
+    (0..desired_samples.div_ceil(CHUNK_SIZE))
         .into_par_iter()
-        .map(|scenario| {
-            let (extra_hole, extra_board) = scenario.split_at(unknown_hole_cards);
-            let extra_players = extra_hole
-                .chunks_exact(HOLE_CARDS_PER_PLAYER)
-                .skip(n_folded)
-                .map(|cards| cards.try_into().unwrap())
+        .map(|chunk| {
+            let mut rng = rng.stream(chunk as u64);
+            let size = CHUNK_SIZE.min(desired_samples - chunk * CHUNK_SIZE);
+            (0..size).fold(new_odds(), |odds, _| {
+                let (players, board, streets) = deal(&mut rng);
+                let odds = streets
+                    .into_iter()
+                    .enumerate()
+                    .fold(odds, |odds, (i, board)| {
+                        odds.update_street(i, payouts(variant, &players, board))
+                    });
+                odds.update(payouts(variant, &players, board), 1)
+                    .update_pots(side_pot_shares(variant, &players, board, pots), 1)
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .fold(new_odds(), Odds::merge)
+}
+
+/// Number of scenarios sampled between checks of a `StoppingRule`.
+const BATCH_SIZE: usize = 10_000;
+
+/// When to stop sampling scenarios at random.
+#[derive(Clone, Copy, Debug)]
+pub struct StoppingRule {
+    pub max_samples: usize,
+    /// In percentage points.
+    pub target_standard_error: Option<f64>,
+    pub deadline: Option<Instant>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    MaxSamples,
+    TargetStandardError,
+    Deadline,
+    Cancelled,
+}
+
+impl StoppingRule {
+    pub fn samples(max_samples: usize) -> Self {
+        StoppingRule {
+            max_samples,
+            target_standard_error: None,
+            deadline: None,
+        }
+    }
+
+    pub fn with_target_standard_error(self, standard_error: f64) -> Self {
+        StoppingRule {
+            target_standard_error: Some(standard_error),
+            ..self
+        }
+    }
+
+    pub fn with_time_limit(self, limit: Duration) -> Self {
+        StoppingRule {
+            deadline: Some(Instant::now() + limit),
+            ..self
+        }
+    }
+
+    fn check(&self, odds: &Odds, samples: usize) -> Option<StopReason> {
+        let precise = |target| {
+            let hero = odds.0.first();
+            hero.map_or(false, |hero| hero.equity_standard_error() <= target)
+        };
+        if samples >= self.max_samples {
+            Some(StopReason::MaxSamples)
+        } else if self.target_standard_error.map_or(false, precise) {
+            Some(StopReason::TargetStandardError)
+        } else if self
+            .deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+        {
+            Some(StopReason::Deadline)
+        } else {
+            None
+        }
+    }
+}
+
+/// Like `odds`, but samples in batches until `rule` is met or `observer` breaks. `observer` is
+/// shown the odds so far, the samples taken and the most that will be.
+pub fn odds_until<R: SplitRng + Sync>(
+    spot: Spot,
+    deck: Deck,
+    rule: StoppingRule,
+    rng: &R,
+    mut observer: impl FnMut(&Odds, usize, usize) -> ControlFlow<()>,
+) -> (Odds, StopReason) {
+    let mut all_odds = Odds::new(spot.opponents + spot.players.len());
+    let mut samples = 0;
+    let mut batches = 0;
+    loop {
+        let batch = BATCH_SIZE.min(rule.max_samples - samples);
+        all_odds = all_odds.merge(odds(spot, deck, batch, &rng.stream(batches)));
+        samples += batch;
+        batches += 1;
+        let cancelled = observer(&all_odds, samples, rule.max_samples).is_break();
+        if let Some(reason) = rule.check(&all_odds, samples) {
+            return (all_odds, reason);
+        }
+        if cancelled {
+            return (all_odds, StopReason::Cancelled);
+        }
+    }
+}
+
+fn deal_ranges<'a>(ranges: &'a [Range], rng: &mut impl Rng<usize>) -> Vec<&'a HoleCards> {
+    loop {
+        let holes = ranges.iter().map(|range| range.sample(rng)).collect_vec();
+        if holes.iter().copied().flatten().all_unique() {
+            return holes;
+        }
+    }
+}
+
+fn remaining_cards(deck: Deck, holes: &Players) -> Vec<Card> {
+    deck.difference(holes.iter().flatten().collect())
+        .consume()
+        .collect()
+}
+
+pub fn assignments(ranges: &[Range]) -> Vec<Vec<HoleCards>> {
+    ranges.iter().fold(vec![vec![]], |assignments, range| {
+        assignments
+            .into_iter()
+            .flat_map(|holes| {
+                range
+                    .combos()
+                    .iter()
+                    .filter(|combo| {
+                        !combo
+                            .iter()
+                            .any(|card| holes.iter().flatten().contains(card))
+                    })
+                    .map(|combo| [&holes[..], &[combo.clone()]].concat())
+                    .collect_vec()
+            })
+            .collect()
+    })
+}
+
+/// Number of distinct scenarios `exhaustive_odds` would evaluate, or `None` on overflow.
+pub fn scenarios(
+    cards_remaining: usize,
+    unknown_board_cards: usize,
+    opponents: usize,
+    hole_cards: usize,
+) -> Option<u64> {
+    let board = choose(cards_remaining, unknown_board_cards)?;
+    (0..opponents).try_fold(board, |total, i| {
+        let remaining = cards_remaining.checked_sub(unknown_board_cards + i * hole_cards)?;
+        total.checked_mul(choose(remaining, hole_cards)?)
+    })
+}
+
+fn choose(n: usize, k: usize) -> Option<u64> {
+    if k > n {
+        return Some(0);
+    }
+    (0..k as u64).try_fold(1u64, |acc, i| {
+        Some(acc.checked_mul(n as u64 - i)? / (i + 1))
+    })
+}
+
+pub fn exhaustive_odds(spot: Spot, deck: Deck) -> Odds {
+    exhaustive_odds_observed(spot, deck, |_, _, _| ControlFlow::Continue(())).unwrap()
+}
+
+/// Like `exhaustive_odds`, but `observer` can break between batches of boards. It is shown the odds
+/// so far, the scenarios evaluated and how many there are in all.
+pub fn exhaustive_odds_observed(
+    spot: Spot,
+    deck: Deck,
+    mut observer: impl FnMut(&Odds, usize, usize) -> ControlFlow<()>,
+) -> Option<Odds> {
+    let unknown_board_cards = spot.variant.board_length() - spot.board.len();
+    let odds = enumerate(spot, deck, unknown_board_cards, &mut observer)?;
+    // each street is enumerated on its own, as if the hand were shown down after it
+    let mut ignore = |_: &Odds, _, _| ControlFlow::Continue(());
+    spot.streets().into_iter().try_fold(odds, |odds, n| {
+        Some(odds.add_street(&enumerate(spot, deck, n, &mut ignore)?))
+    })
+}
+
+const BOARDS_PER_BATCH: usize = 4096;
+
+fn enumerate(
+    spot: Spot,
+    deck: Deck,
+    unknown_board_cards: usize,
+    observer: &mut impl FnMut(&Odds, usize, usize) -> ControlFlow<()>,
+) -> Option<Odds> {
+    let Spot {
+        players,
+        board,
+        opponents,
+        ..
+    } = spot;
+    let new_odds = || Odds::new(opponents + players.len());
+    let holes = assignments(players)
+        .into_iter()
+        .map(|holes| {
+            let masks = holes.iter().map(|hole| mask(hole)).collect_vec();
+            (masks, remaining_cards(deck, &holes))
+        })
+        .collect_vec();
+    let boards = holes
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(i, (masks, remaining))| {
+            let symmetries = symmetries(masks, mask(board));
+            remaining
+                .iter()
+                .copied()
+                .combinations(unknown_board_cards)
+                .filter_map(move |extra_board| {
+                    // only the first of the boards that differ by a symmetry is evaluated, on
+                    // behalf of them all
+                    let cards = mask(&extra_board);
+                    let images = symmetries
+                        .iter()
+                        .map(|permutation| permutation.apply_mask(cards))
+                        .collect_vec();
+                    let first = images.iter().all(|image| *image >= cards);
+                    first.then(|| (i, cards, images.into_iter().unique().count() as u64))
+                })
+        })
+        .collect::<Vec<_>>();
+    let deals = holes
+        .iter()
+        .map(|(_, remaining)| {
+            let rest = remaining.len() - unknown_board_cards;
+            scenarios(rest, 0, opponents, spot.variant.hole_cards()).unwrap_or(u64::MAX)
+        })
+        .collect_vec();
+    let total = boards
+        .iter()
+        .map(|(i, _, count)| count.saturating_mul(deals[*i]))
+        .fold(0, u64::saturating_add);
+    let mut odds = new_odds();
+    for batch in boards.chunks(BOARDS_PER_BATCH) {
+        let batch_odds = batch
+            .par_iter()
+            .map(|(i, extra_board, count)| {
+                let (masks, remaining) = &holes[*i];
+                let rest = remaining
+                    .iter()
+                    .filter(|card| card.mask() & extra_board == 0)
+                    .copied()
+                    .collect_vec();
+                deal_opponents(
+                    spot,
+                    new_odds(),
+                    &mut masks.clone(),
+                    mask(board) | extra_board,
+                    &rest,
+                    opponents,
+                    *count,
+                )
+            })
+            .reduce(new_odds, Odds::merge);
+        odds = odds.merge(batch_odds);
+        if observer(&odds, odds.scenarios() as usize, total as usize).is_break() {
+            return None;
+        }
+    }
+    Some(odds)
+}
+
+fn deal_opponents(
+    spot: Spot,
+    odds: Odds,
+    players: &mut Vec<u64>,
+    board: u64,
+    remaining: &Cards,
+    opponents: usize,
+    count: u64,
+) -> Odds {
+    let variant = spot.variant;
+    if opponents == 0 {
+        return odds
+            .update(payouts(variant, players, board), count)
+            .update_pots(side_pot_shares(variant, players, board, spot.pots), count);
+    }
+    remaining
+        .iter()
+        .copied()
+        .combinations(variant.hole_cards())
+        .fold(odds, |odds, hole_cards| {
+            let rest = remaining
+                .iter()
+                .filter(|card| !hole_cards.contains(card))
+                .copied()
                 .collect_vec();
+            players.push(mask(&hole_cards));
+            let odds = deal_opponents(spot, odds, players, board, &rest, opponents - 1, count);
+            players.pop();
+            odds
+        })
+}
+
+pub fn outcomes(
+    variant: Variant,
+    players: &Players,
+    board: &Cards,
+) -> impl Iterator<Item = HandOutcome> {
+    let masks = players
+        .iter()
+        .map(|hole_cards| mask(hole_cards))
+        .collect_vec();
+    let ranks = variant.evaluate_all(&masks, mask(board));
+    let lows = variant.evaluate_all_low(&masks, mask(board));
+    hand_outcomes(variant.hands(players, board), &ranks, lows)
+}
 
-            let all_players = [players, &extra_players].concat();
-            let community_cards = [board, extra_board].concat();
-            outcomes(&all_players, &community_cards)
+fn hand_outcomes<H: Ord>(
+    hands: Vec<Hand>,
+    highs: &[H],
+    lows: Option<Vec<Option<LowRank>>>,
+) -> impl Iterator<Item = HandOutcome> {
+    let high = showdown(highs);
+    let low = lows.as_deref().map(low_showdown);
+    let shares = pot_shares(&high, low.as_deref());
+    zip(zip(high, hands), shares)
+        .enumerate()
+        .map(move |(i, ((outcome, hand), share))| HandOutcome {
+            outcome,
+            hand,
+            low: low
+                .as_ref()
+                .zip(lows.as_ref())
+                .map(|(low, lows)| (low[i], lows[i])),
+            share,
         })
-        .fold(new_odds, Odds::update)
-        .reduce(new_odds, Odds::merge)
 }
 
-pub fn outcomes(players: &Players, board: &Cards) -> impl Iterator<Item = HandOutcome> {
-    hand_outcomes(hands(players, board))
+fn payouts(variant: Variant, players: &[u64], board: u64) -> impl Iterator<Item = Payout> {
+    let ranks = variant.evaluate_all(players, board);
+    let low = variant
+        .evaluate_all_low(players, board)
+        .map(|lows| low_showdown(&lows));
+    let high = showdown(&ranks);
+    let shares = pot_shares(&high, low.as_deref());
+    (0..ranks.len()).map(move |i| Payout {
+        outcome: high[i],
+        hand_type: ranks[i].hand_type(),
+        low: low.as_ref().map(|low| low[i]),
+        share: shares[i],
+    })
+}
+
+pub(crate) fn side_pot_shares(
+    variant: Variant,
+    players: &[u64],
+    board: u64,
+    pots: &[SidePot],
+) -> Vec<Vec<f64>> {
+    if pots.is_empty() {
+        return vec![];
+    }
+    let ranks = variant.evaluate_all(players, board);
+    let lows = variant.evaluate_all_low(players, board);
+    pots.iter()
+        .map(|pot| {
+            let high = showdown(&pot.eligible.iter().map(|i| ranks[*i]).collect_vec());
+            let low = lows
+                .as_ref()
+                .map(|lows| low_showdown(&pot.eligible.iter().map(|i| lows[*i]).collect_vec()));
+            let mut shares = vec![0.0; players.len()];
+            for (i, share) in zip(&pot.eligible, pot_shares(&high, low.as_deref())) {
+                shares[*i] = share;
+            }
+            shares
+        })
+        .collect()
 }
 
-fn hand_outcomes(hands: Vec<Hand>) -> impl Iterator<Item = HandOutcome> {
-    let max = hands.iter().max().cloned();
-    let n_winners = hands.iter().filter(|x| Some(*x) == max.as_ref()).count();
+fn showdown<H: Ord>(hands: &[H]) -> Vec<Outcome> {
+    let max = hands.iter().max();
+    let n_winners = hands.iter().filter(|x| Some(*x) == max).count();
     let win = if n_winners == 1 { Win } else { Tie };
+    hands
+        .iter()
+        .map(|hand| if Some(hand) == max { win } else { Loss })
+        .collect()
+}
 
-    hands.into_iter().map(move |hand| HandOutcome {
-        outcome: if Some(&hand) == max.as_ref() {
-            win
-        } else {
-            Loss
-        },
-        hand,
-    })
+fn low_showdown(lows: &[Option<LowRank>]) -> Vec<Outcome> {
+    if lows.iter().all(Option::is_none) {
+        return vec![Loss; lows.len()];
+    }
+    showdown(lows)
+}
+
+fn pot_shares(high: &[Outcome], low: Option<&[Outcome]>) -> Vec<f64> {
+    let winners = |outcomes: &[Outcome]| outcomes.iter().filter(|o| **o != Loss).count() as f64;
+    let low = low.filter(|low| winners(low) > 0.0);
+    let halves = if low.is_some() { 2.0 } else { 1.0 };
+    let high_share = 1.0 / halves / winners(high);
+    high.iter()
+        .enumerate()
+        .map(|(i, outcome)| {
+            let high = if *outcome != Loss { high_share } else { 0.0 };
+            let low = low.map_or(0.0, |low| match low[i] {
+                Loss => 0.0,
+                _ => 0.5 / winners(low),
+            });
+            high + low
+        })
+        .collect()
 }
 
 pub struct HandOutcome {
     pub outcome: Outcome,
     pub hand: Hand,
+    /// The outcome for the low half and the player's low, in hi/lo games.
+    pub low: Option<(Outcome, Option<LowRank>)>,
+    /// The fraction of the pot won.
+    pub share: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Payout {
+    pub outcome: Outcome,
+    pub hand_type: HandType,
+    pub low: Option<Outcome>,
+    pub share: f64,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -86,10 +546,14 @@ impl Odds {
         Self((0..num_players as u64).map(HandOdds::new).collect())
     }
 
-    fn update(self, outcomes: impl Iterator<Item = HandOutcome>) -> Self {
+    pub(crate) fn from_hands(hands: Vec<HandOdds>) -> Self {
+        Self(hands)
+    }
+
+    fn update(self, payouts: impl Iterator<Item = Payout>, count: u64) -> Self {
         Self(
-            zip(outcomes, self.0)
-                .map(|(outcome, odds)| odds.update(outcome))
+            zip(payouts, self.0)
+                .map(|(payout, odds)| odds.update_times(payout, count))
                 .collect(),
         )
     }
@@ -102,6 +566,45 @@ impl Odds {
         )
     }
 
+    fn update_street(mut self, i: usize, payouts: impl Iterator<Item = Payout>) -> Self {
+        for (odds, payout) in zip(&mut self.0, payouts) {
+            odds.add_to_street(i, (payout.share, 1));
+        }
+        self
+    }
+
+    fn update_pots(self, shares: Vec<Vec<f64>>, count: u64) -> Self {
+        if shares.is_empty() {
+            return self;
+        }
+        Self(
+            self.0
+                .into_iter()
+                .enumerate()
+                .map(|(i, odds)| odds.update_pots(shares.iter().map(|pot| pot[i]), count))
+                .collect(),
+        )
+    }
+
+    fn add_street(self, street: &Odds) -> Self {
+        Self(
+            zip(self.0, &street.0)
+                .map(|(mut odds, street)| {
+                    odds.streets.push((street.pot_share, street.all()));
+                    odds
+                })
+                .collect(),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HandOdds> {
+        self.0.iter()
+    }
+
+    pub fn scenarios(&self) -> u64 {
+        self.0.first().map_or(0, HandOdds::all)
+    }
+
     pub fn merge_unknown_players(mut self, n: usize) -> Self {
         if self.0.len() - n <= 1 {
             return self;
@@ -134,6 +637,16 @@ pub struct HandOdds {
     wins: u64,
     ties: u64,
     losses: u64,
+    scoops: u64,
+    splits: u64,
+    quarters: u64,
+    low_wins: u64,
+    pot_share: f64,
+    pot_share_squared: f64,
+    /// Per street to come, the summed pot share and the number of scenarios.
+    streets: Vec<(f64, u64)>,
+    /// The summed share of each side pot.
+    pots: Vec<f64>,
     distribution: HandTypeDistribution,
 }
 
@@ -141,8 +654,13 @@ pub struct HandOdds {
 pub struct HandTypeDistribution(HashMap<HandType, u64>);
 
 impl HandTypeDistribution {
-    pub fn update(mut self, hand_type: HandType) -> Self {
-        *self.0.entry(hand_type).or_insert(0) += 1;
+    #[cfg(test)]
+    pub fn update(self, hand_type: HandType) -> Self {
+        self.add(hand_type, 1)
+    }
+
+    pub fn add(mut self, hand_type: HandType, count: u64) -> Self {
+        *self.0.entry(hand_type).or_insert(0) += count;
         self
     }
 
@@ -166,20 +684,90 @@ impl HandOdds {
             wins: 0,
             ties: 0,
             losses: 0,
+            scoops: 0,
+            splits: 0,
+            quarters: 0,
+            low_wins: 0,
+            pot_share: 0.0,
+            pot_share_squared: 0.0,
+            streets: vec![],
+            pots: vec![],
             distribution: Default::default(),
         }
     }
 
-    pub fn update(mut self, outcome: HandOutcome) -> Self {
-        match outcome.outcome {
-            Win => self.wins += 1,
-            Tie => self.ties += 1,
-            Loss => self.losses += 1,
+    /// Odds known only as win and tie rates and the mean and mean square of the pot share, over
+    /// `samples` scenarios.
+    pub(crate) fn from_rates(
+        id: u64,
+        win: f64,
+        tie: f64,
+        equity: f64,
+        equity_squared: f64,
+        samples: u64,
+    ) -> Self {
+        let wins = (win * samples as f64).round() as u64;
+        let ties = ((tie * samples as f64).round() as u64).min(samples - wins);
+        Self {
+            wins,
+            ties,
+            losses: samples - wins - ties,
+            pot_share: equity * samples as f64,
+            pot_share_squared: equity_squared * samples as f64,
+            ..Self::new(id)
+        }
+    }
+
+    pub fn update(self, payout: Payout) -> Self {
+        self.update_times(payout, 1)
+    }
+
+    pub fn update_times(mut self, payout: Payout, count: u64) -> Self {
+        match payout.outcome {
+            Win => self.wins += count,
+            Tie => self.ties += count,
+            Loss => self.losses += count,
+        }
+        match payout.share {
+            share if share == 1.0 => self.scoops += count,
+            share if share == 0.0 => {}
+            share => {
+                self.splits += count;
+                if share == 0.25 {
+                    self.quarters += count;
+                }
+            }
+        }
+        if matches!(payout.low, Some(Win | Tie)) {
+            self.low_wins += count;
+        }
+        self.pot_share += payout.share * count as f64;
+        self.pot_share_squared += payout.share * payout.share * count as f64;
+        self.distribution = self.distribution.add(payout.hand_type, count);
+        self
+    }
+
+    pub fn update_pots(mut self, shares: impl Iterator<Item = f64>, count: u64) -> Self {
+        for (i, share) in shares.enumerate() {
+            self.add_to_pot(i, share * count as f64);
         }
-        self.distribution = self.distribution.update(outcome.hand.hand_type);
         self
     }
 
+    fn add_to_pot(&mut self, i: usize, share: f64) {
+        match self.pots.get_mut(i) {
+            Some(pot) => *pot += share,
+            None => self.pots.push(share),
+        }
+    }
+
+    fn add_to_street(&mut self, i: usize, (share, count): (f64, u64)) {
+        match self.streets.get_mut(i) {
+            Some(street) => *street = (street.0 + share, street.1 + count),
+            None => self.streets.push((share, count)),
+        }
+    }
+
     pub fn merge(mut self, other: HandOdds) -> Self {
         self.who = match (self.who, other.who) {
             (Player::Single(id1), Player::Single(id2)) if id1 == id2 => Player::Single(id1),
@@ -191,6 +779,18 @@ impl HandOdds {
         self.wins += other.wins;
         self.ties += other.ties;
         self.losses += other.losses;
+        self.scoops += other.scoops;
+        self.splits += other.splits;
+        self.quarters += other.quarters;
+        self.low_wins += other.low_wins;
+        self.pot_share += other.pot_share;
+        self.pot_share_squared += other.pot_share_squared;
+        for (i, street) in other.streets.into_iter().enumerate() {
+            self.add_to_street(i, street);
+        }
+        for (i, pot) in other.pots.into_iter().enumerate() {
+            self.add_to_pot(i, pot);
+        }
         self.distribution = self.distribution.merge(other.distribution);
         self
     }
@@ -207,42 +807,127 @@ impl HandOdds {
         100f64 * (self.losses as f64 / self.all() as f64)
     }
 
+    pub fn scoop_percent(&self) -> f64 {
+        100f64 * (self.scoops as f64 / self.all() as f64)
+    }
+
+    pub fn split_percent(&self) -> f64 {
+        100f64 * (self.splits as f64 / self.all() as f64)
+    }
+
+    pub fn quartered_percent(&self) -> f64 {
+        100f64 * (self.quarters as f64 / self.all() as f64)
+    }
+
+    pub fn low_win_percent(&self) -> f64 {
+        100f64 * (self.low_wins as f64 / self.all() as f64)
+    }
+
+    /// The average fraction of the pot won, counting a pot split `n` ways as `1/n` of a win.
+    pub fn equity(&self) -> f64 {
+        self.pot_share / self.all() as f64
+    }
+
+    /// The mean square of the fraction of the pot won.
+    pub(crate) fn equity_squared(&self) -> f64 {
+        self.pot_share_squared / self.all() as f64
+    }
+
+    pub fn equity_percent(&self) -> f64 {
+        100f64 * self.equity()
+    }
+
     pub fn all(&self) -> u64 {
         self.wins + self.ties + self.losses
     }
 
-    pub fn distribution(&self) -> impl Iterator<Item = (&HandType, f64)> {
+    /// The equity as a percentage had the hand been shown down after each street to come.
+    pub fn street_equity_percent(&self) -> impl Iterator<Item = f64> + '_ {
+        self.streets
+            .iter()
+            .map(|(share, count)| 100f64 * share / *count as f64)
+    }
+
+    /// The average fraction of each of `pots` pots won, main pot first.
+    pub fn pot_equity(&self, pots: usize) -> impl Iterator<Item = f64> + '_ {
+        (0..pots).map(|i| self.pots.get(i).unwrap_or(&0.0) / self.all() as f64)
+    }
+
+    /// Each hand type made, most frequent first, as a percentage with its standard error.
+    pub fn distribution(&self) -> impl Iterator<Item = (&HandType, f64, f64)> {
         self.distribution
             .0
             .iter()
             .sorted_by_key(|(_hand_type, count)| Reverse(**count))
-            .map(|(hand_type, count)| (hand_type, 100f64 * (*count as f64 / self.all() as f64)))
+            .map(|(hand_type, count)| {
+                let percent = 100f64 * (*count as f64 / self.all() as f64);
+                (hand_type, percent, self.standard_error(*count))
+            })
+    }
+
+    pub fn win_standard_error(&self) -> f64 {
+        self.standard_error(self.wins)
+    }
+
+    pub fn tie_standard_error(&self) -> f64 {
+        self.standard_error(self.ties)
+    }
+
+    pub fn loss_standard_error(&self) -> f64 {
+        self.standard_error(self.losses)
+    }
+
+    pub fn equity_standard_error(&self) -> f64 {
+        let n = self.all() as f64;
+        let mean = self.pot_share / n;
+        let variance = (self.pot_share_squared / n - mean * mean).max(0.0);
+        100f64 * (variance / n).sqrt()
+    }
+
+    fn standard_error(&self, count: u64) -> f64 {
+        let n = self.all() as f64;
+        let p = count as f64 / n;
+        100f64 * (p * (1.0 - p) / n).sqrt()
     }
 }
 
+/// The 95% confidence interval around an estimated percentage, given its standard error.
+pub fn confidence_interval(percent: f64, standard_error: f64) -> (f64, f64) {
+    let margin = Z_95 * standard_error;
+    ((percent - margin).max(0.0), (percent + margin).min(100.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::card::Card;
+    use crate::card::parse_cards;
     use crate::hand::{gen_flushes, gen_straights, hand};
 
-    fn parse_card(raw: &str) -> Card {
+    fn parse_range(raw: &str) -> Range {
         raw.parse().unwrap()
     }
 
-    fn parse_cards(raw: &str) -> Vec<Card> {
-        raw.split(' ').map(parse_card).collect()
+    fn spot<'a>(players: &'a [Range], board: &'a Cards, opponents: usize) -> Spot<'a> {
+        Spot {
+            variant: Variant::Holdem,
+            players,
+            board,
+            opponents,
+            n_folded: 0,
+            streets: false,
+            pots: &[],
+        }
     }
 
     fn winners(hands: &[Hand]) -> Vec<Hand> {
-        hand_outcomes(hands.to_vec())
+        hand_outcomes(hands.to_vec(), hands, None)
             .filter(|o| o.outcome == Win)
             .map(|o| o.hand)
             .collect()
     }
 
     fn ties(hands: &[Hand]) -> Vec<Hand> {
-        hand_outcomes(hands.to_vec())
+        hand_outcomes(hands.to_vec(), hands, None)
             .filter(|o| o.outcome == Tie)
             .map(|o| o.hand)
             .collect()
@@ -325,4 +1010,363 @@ mod tests {
         assert!(winners.contains(&hands[0]));
         assert_eq!(winners.len(), 1);
     }
+
+    #[test]
+    fn test_scenarios() {
+        assert_eq!(Some(44), scenarios(44, 1, 0, 2));
+        assert_eq!(Some(990), scenarios(45, 2, 0, 2));
+        assert_eq!(Some(1_712_304), scenarios(48, 5, 0, 2));
+        assert_eq!(Some(46 * 990), scenarios(46, 1, 1, 2));
+        assert_eq!(Some(42 * 101_270), scenarios(42, 1, 1, 4));
+        assert_eq!(None, scenarios(52, 5, 8, 2));
+    }
+
+    #[test]
+    fn test_exhaustive_odds_on_the_turn() {
+        let players = [parse_range("AdKs"), parse_range("8h8s")];
+        let board = parse_cards("8c Ah As 9c");
+        let mut deck = Deck::default();
+        for card in players
+            .iter()
+            .flat_map(|range| range.combos()[0].clone())
+            .chain(board.clone())
+        {
+            deck.remove(&card).unwrap();
+        }
+        let odds = exhaustive_odds(spot(&players, &board, 0), deck)
+            .into_iter()
+            .collect_vec();
+        assert_eq!((7, 0, 37), (odds[0].wins, odds[0].ties, odds[0].losses));
+        assert_eq!((37, 0, 7), (odds[1].wins, odds[1].ties, odds[1].losses));
+    }
+
+    #[test]
+    fn test_exhaustive_odds_with_opponent_on_the_river() {
+        let players = [parse_range("AdKs")];
+        let board = parse_cards("8c Ah As 9c 9h");
+        let mut deck = Deck::default();
+        for card in players
+            .iter()
+            .flat_map(|range| range.combos()[0].clone())
+            .chain(board.clone())
+        {
+            deck.remove(&card).unwrap();
+        }
+        let odds = exhaustive_odds(spot(&players, &board, 1), deck)
+            .into_iter()
+            .collect_vec();
+        assert_eq!(990, odds[0].all());
+        assert_eq!(odds[0].wins, odds[1].losses);
+        assert_eq!(odds[0].ties, odds[1].ties);
+    }
+
+    #[test]
+    fn test_exhaustive_progress_counts_every_scenario() {
+        let players = [parse_range("AdKs")];
+        let board = parse_cards("8c Ah As 9c");
+        let mut deck = Deck::default();
+        for card in &board {
+            deck.remove(card).unwrap();
+        }
+        let mut snapshots = vec![];
+        let odds =
+            exhaustive_odds_observed(spot(&players, &board, 1), deck, |odds, scenarios, total| {
+                snapshots.push((odds.scenarios(), scenarios, total));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(46 * 990, odds.scenarios());
+        assert_eq!(vec![(46 * 990, 46 * 990, 46 * 990)], snapshots);
+    }
+
+    #[test]
+    fn test_assignments_exclude_collisions() {
+        let ranges = [parse_range("AA"), parse_range("AKs")];
+        let assignments = assignments(&ranges);
+        assert_eq!(12, assignments.len());
+        assert!(assignments
+            .iter()
+            .all(|holes| holes.iter().flatten().all_unique()));
+    }
+
+    #[test]
+    fn test_range_vs_range() {
+        let players = [parse_range("AA"), parse_range("KK, QQ")];
+        let board = parse_cards("2c 3d 7h 8s Tc");
+        let mut deck = Deck::default();
+        for card in &board {
+            deck.remove(card).unwrap();
+        }
+        let odds = exhaustive_odds(spot(&players, &board, 0), deck)
+            .into_iter()
+            .collect_vec();
+        assert_eq!((72, 0, 0), (odds[0].wins, odds[0].ties, odds[0].losses));
+    }
+
+    #[test]
+    fn test_sampled_ranges_never_collide() {
+        let ranges = [parse_range("AA"), parse_range("AK"), parse_range("KK+")];
+        let mut rng = fastrand::Rng::with_seed(1);
+        for _ in 0..1000 {
+            assert!(deal_ranges(&ranges, &mut rng)
+                .iter()
+                .copied()
+                .flatten()
+                .all_unique());
+        }
+    }
+
+    #[test]
+    fn test_sampled_board_never_repeats_hole_cards() {
+        // a range narrowed to one live combo, whose cards were never removed from the deck
+        let players = [parse_range("AhKh"), parse_range("AcAd")];
+        let keep = parse_cards("Ac Ad 2c 3d 4h 8s 9c");
+        let mut deck = Deck::default();
+        for card in Deck::default()
+            .consume()
+            .filter(|card| !keep.contains(card))
+        {
+            deck.remove(&card).unwrap();
+        }
+        let rng = fastrand::Rng::with_seed(1);
+        let odds = odds(spot(&players, &[], 0), deck, 100, &rng)
+            .into_iter()
+            .collect_vec();
+        // the only board left is 2c 3d 4h 8s 9c
+        assert_eq!(100, odds[1].distribution.frequency_of(&HandType::Pair));
+    }
+
+    #[test]
+    fn test_exhaustive_omaha_odds_on_the_river() {
+        let players = [parse_range("AhKh2c3d"), parse_range("QsQdJcJd")];
+        let board = parse_cards("Qh 7h 4s 9c");
+        let mut deck = Deck::default();
+        for card in players
+            .iter()
+            .flat_map(|range| range.combos()[0].clone())
+            .chain(board.clone())
+        {
+            deck.remove(&card).unwrap();
+        }
+        let spot = Spot {
+            variant: Variant::Omaha,
+            ..spot(&players, &board, 0)
+        };
+        let odds = exhaustive_odds(spot, deck).into_iter().collect_vec();
+        assert_eq!(40, odds[0].all());
+        // nine hearts remain, but the 4h and 9h pair the board and fill up the set
+        assert_eq!((7, 0, 33), (odds[0].wins, odds[0].ties, odds[0].losses));
+    }
+
+    #[test]
+    fn test_pot_shares() {
+        assert_eq!(vec![1.0, 0.0], pot_shares(&[Win, Loss], None));
+        assert_eq!(vec![0.5, 0.5, 0.0], pot_shares(&[Tie, Tie, Loss], None));
+        let low = [Loss, Tie, Tie];
+        assert_eq!(
+            vec![0.5, 0.25, 0.25],
+            pot_shares(&[Win, Loss, Loss], Some(&low))
+        );
+        let low = [Loss, Loss, Loss];
+        assert_eq!(
+            vec![1.0, 0.0, 0.0],
+            pot_shares(&[Win, Loss, Loss], Some(&low))
+        );
+    }
+
+    #[test]
+    fn test_exhaustive_hi_lo_odds_on_the_river() {
+        let players = [parse_range("AhKh2c3d"), parse_range("QsQdJcJd")];
+        let board = parse_cards("Qh 7h 4s 9c");
+        let mut deck = Deck::default();
+        for card in players
+            .iter()
+            .flat_map(|range| range.combos()[0].clone())
+            .chain(board.clone())
+        {
+            deck.remove(&card).unwrap();
+        }
+        let spot = Spot {
+            variant: Variant::OmahaHiLo,
+            ..spot(&players, &board, 0)
+        };
+        let odds = exhaustive_odds(spot, deck).into_iter().collect_vec();
+        // 21 rivers make a low, 5 of them hearts; the Th and Jh make a flush without one
+        assert_eq!((7, 21), (odds[0].wins, odds[0].low_wins));
+        assert_eq!(
+            (7, 16, 0),
+            (odds[0].scoops, odds[0].splits, odds[0].quarters)
+        );
+        assert_eq!(100.0 * 15.0 / 40.0, odds[0].equity_percent());
+        assert_eq!(100.0 * 25.0 / 40.0, odds[1].equity_percent());
+    }
+
+    #[test]
+    fn test_equity_of_a_split_pot() {
+        let board = parse_cards("As Ks Qs Js Ts");
+        let odds = |players: &[Range]| {
+            let mut deck = Deck::default();
+            for card in players
+                .iter()
+                .flat_map(|range| range.combos()[0].clone())
+                .chain(board.clone())
+            {
+                deck.remove(&card).unwrap();
+            }
+            exhaustive_odds(spot(players, &board, 0), deck)
+                .into_iter()
+                .collect_vec()
+        };
+        // everyone plays the royal flush on board
+        let players = [
+            parse_range("2c3c"),
+            parse_range("2d3d"),
+            parse_range("4h5h"),
+        ];
+        let three_way = odds(&players);
+        assert_eq!(
+            (0, 1, 0),
+            (three_way[0].wins, three_way[0].ties, three_way[0].losses)
+        );
+        assert!((three_way[0].equity() - 1.0 / 3.0).abs() < 1e-12);
+        let four_way = odds(&[players.as_slice(), &[parse_range("6h7h")]].concat());
+        assert_eq!(100.0, four_way[0].tie_percent());
+        assert!((four_way[0].equity() - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_standard_error() {
+        let mut odds = HandOdds::new(0);
+        for i in 0..10_000 {
+            let (outcome, share) = if i % 4 == 0 { (Win, 1.0) } else { (Loss, 0.0) };
+            odds = odds.update(Payout {
+                outcome,
+                hand_type: HandType::Pair,
+                low: None,
+                share,
+            });
+        }
+        // sqrt(0.25 * 0.75 / 10_000) = 0.433%
+        assert!((odds.win_standard_error() - 0.433).abs() < 1e-3);
+        assert!((odds.equity_standard_error() - 0.433).abs() < 1e-3);
+        assert_eq!(0.0, odds.tie_standard_error());
+        let (low, high) = confidence_interval(odds.win_percent(), odds.win_standard_error());
+        assert!((low - 24.151).abs() < 1e-3 && (high - 25.849).abs() < 1e-3);
+        assert_eq!((0.0, 100.0), confidence_interval(50.0, 30.0));
+    }
+
+    #[test]
+    fn test_odds_until() {
+        let players = [Range::from(parse_cards("As Ah"))];
+        let spot = spot(&players, &[], 1);
+        let rng = fastrand::Rng::with_seed(1);
+        let (odds, reason) = odds_until(
+            spot,
+            Deck::default(),
+            StoppingRule::samples(25_000),
+            &rng,
+            |_, _, _| ControlFlow::Continue(()),
+        );
+        assert_eq!((25_000, StopReason::MaxSamples), (odds.scenarios(), reason));
+
+        let rule = StoppingRule::samples(1_000_000).with_target_standard_error(0.2);
+        let rng = fastrand::Rng::with_seed(1);
+        let (odds, reason) = odds_until(spot, Deck::default(), rule, &rng, |_, _, _| {
+            ControlFlow::Continue(())
+        });
+        assert_eq!(StopReason::TargetStandardError, reason);
+        assert!(odds.scenarios() < 1_000_000);
+        assert!(odds.0[0].equity_standard_error() <= 0.2);
+
+        let rule = StoppingRule::samples(1_000_000).with_time_limit(Duration::ZERO);
+        let rng = fastrand::Rng::with_seed(1);
+        let (odds, reason) = odds_until(spot, Deck::default(), rule, &rng, |_, _, _| {
+            ControlFlow::Continue(())
+        });
+        assert_eq!((10_000, StopReason::Deadline), (odds.scenarios(), reason));
+    }
+
+    #[test]
+    fn test_sampled_odds_are_independent_of_thread_count() {
+        let players = [parse_range("QQ+,AKs"), parse_range("JJ-99,AQo")];
+        let rng = fastrand::Rng::with_seed(7);
+        let run = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let spot = spot(&players, &[], 1);
+            let odds = pool.install(|| odds(spot, Deck::default(), 3 * CHUNK_SIZE + 1, &rng));
+            odds.0
+                .iter()
+                .map(|odds| (odds.wins, odds.ties, odds.losses, odds.pot_share.to_bits()))
+                .collect_vec()
+        };
+        let first = run(1);
+        assert_eq!(first, run(4));
+        assert!(first
+            .iter()
+            .all(|(w, t, l, _)| w + t + l == 3 * CHUNK_SIZE as u64 + 1));
+    }
+
+    #[test]
+    fn test_cancel_sampling() {
+        let players = [Range::from(parse_cards("As Ah"))];
+        let mut snapshots = vec![];
+        let (odds, reason) = odds_until(
+            spot(&players, &[], 1),
+            Deck::default(),
+            StoppingRule::samples(1_000_000),
+            &fastrand::Rng::with_seed(1),
+            |odds, samples, max_samples| {
+                snapshots.push((odds.scenarios(), samples as u64, max_samples));
+                match samples >= 3 * BATCH_SIZE {
+                    true => ControlFlow::Break(()),
+                    false => ControlFlow::Continue(()),
+                }
+            },
+        );
+        assert_eq!(StopReason::Cancelled, reason);
+        assert_eq!(3 * BATCH_SIZE as u64, odds.scenarios());
+        assert_eq!(
+            vec![
+                (10_000, 10_000, 1_000_000),
+                (20_000, 20_000, 1_000_000),
+                (30_000, 30_000, 1_000_000)
+            ],
+            snapshots
+        );
+    }
+
+    #[test]
+    fn test_street_equity() {
+        let players = [
+            Range::from(parse_cards("Ad Ks")),
+            Range::from(parse_cards("8h 8s")),
+        ];
+        let board = parse_cards("8c Ah As");
+        let deck = Deck::default().difference(
+            players
+                .iter()
+                .filter_map(Range::fixed)
+                .flatten()
+                .chain(&board)
+                .collect(),
+        );
+        let spot = Spot {
+            streets: true,
+            ..spot(&players, &board, 0)
+        };
+        // player 1 only gets ahead on the turn with the last ace or one of three kings
+        let exact = exhaustive_odds(spot, deck).into_iter().collect_vec();
+        let turn = exact[0].street_equity_percent().collect_vec();
+        assert_eq!(1, turn.len());
+        assert!((turn[0] - 100.0 * 4.0 / 45.0).abs() < 1e-9);
+
+        let rng = fastrand::Rng::with_seed(1);
+        let sampled = odds(spot, deck, 10_000, &rng).into_iter().collect_vec();
+        let turn = sampled[0].street_equity_percent().collect_vec();
+        assert_eq!(1, turn.len());
+        assert!((turn[0] - 100.0 * 4.0 / 45.0).abs() < 1.0);
+    }
 }
@@ -1,11 +1,12 @@
 use Rank::*;
 use Suit::*;
 
-pub const HOLE_CARDS_PER_PLAYER: usize = 2;
-
 pub type Players = [HoleCards];
 pub type Cards = [Card];
-pub type HoleCards = [Card; HOLE_CARDS_PER_PLAYER];
+pub type HoleCards = Vec<Card>;
+
+/// Number of cards of each suit, i.e. bits per suit in a card mask.
+pub const RANKS: u32 = 13;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 pub struct Card {
@@ -13,6 +14,28 @@ pub struct Card {
     pub suit: Suit,
 }
 
+impl Card {
+    /// The position of this card in a 52-bit mask: 13 bits per suit, deuce first.
+    pub fn index(&self) -> u32 {
+        self.suit as u32 * RANKS + (self.rank as u32 - Deuce as u32)
+    }
+
+    pub fn from_index(index: u32) -> Self {
+        Self {
+            rank: Rank::ALL[(index % RANKS) as usize],
+            suit: Suit::ALL[(index / RANKS) as usize],
+        }
+    }
+
+    pub fn mask(&self) -> u64 {
+        1 << self.index()
+    }
+}
+
+pub fn mask(cards: &Cards) -> u64 {
+    cards.iter().fold(0, |mask, card| mask | card.mask())
+}
+
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum Rank {
@@ -36,7 +59,6 @@ impl Rank {
         Deuce, Trey, Four, Five, Six, Seven, Eight, Nine, Ten, Jack, Queen, King, Ace,
     ];
 
-    #[cfg(test)]
     pub const ALL_WITH_BOTH_ACES: [Rank; 14] = [
         Ace, Deuce, Trey, Four, Five, Six, Seven, Eight, Nine, Ten, Jack, Queen, King, Ace,
     ];
@@ -53,3 +75,9 @@ pub enum Suit {
 impl Suit {
     pub const ALL: [Suit; 4] = [Hearts, Clubs, Spades, Diamonds];
 }
+
+/// Parses cards separated by spaces, e.g. `"As Kd"`.
+#[cfg(test)]
+pub(crate) fn parse_cards(raw: &str) -> Vec<Card> {
+    raw.split(' ').map(|card| card.parse().unwrap()).collect()
+}
@@ -1,10 +1,12 @@
-use std::collections::HashSet;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 
-use crate::card::{Card, Rank, Suit};
+use crate::card::{Card, Rank::Six, RANKS};
+use crate::floyd::{sample, Rng};
 
-pub struct Deck(HashSet<Card>);
+/// A set of cards, stored as a 52-bit mask and always iterated in the same canonical order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Deck(u64);
 
 #[derive(Debug)]
 pub enum DeckError {
@@ -20,40 +22,179 @@ impl Display for DeckError {
 impl Error for DeckError {}
 
 impl Deck {
+    pub const EMPTY: Deck = Deck(0);
+
+    /// The 36-card short deck, with the deuce to five of each suit removed.
+    pub fn short() -> Self {
+        Deck::default()
+            .iter()
+            .filter(|card| card.rank >= Six)
+            .collect()
+    }
+
     pub fn remove(&mut self, card: &Card) -> Result<(), DeckError> {
-        if self.0.remove(card) {
+        if self.contains(card) {
+            self.0 &= !card.mask();
             Ok(())
         } else {
             Err(DeckError::DuplicateCard(*card))
         }
     }
 
+    pub fn insert(&mut self, card: &Card) -> Result<(), DeckError> {
+        if self.contains(card) {
+            Err(DeckError::DuplicateCard(*card))
+        } else {
+            self.0 |= card.mask();
+            Ok(())
+        }
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        self.0 & card.mask() != 0
+    }
+
+    /// Removes `n` random cards from the deck and returns them in the order they were dealt.
+    pub fn deal(&mut self, n: usize, rng: &mut impl Rng<usize>) -> Vec<Card> {
+        let cards = sample(n, self.len(), rng)
+            .into_iter()
+            .map(|i| self.nth(i))
+            .collect::<Vec<_>>();
+        for card in &cards {
+            self.0 &= !card.mask();
+        }
+        cards
+    }
+
+    pub fn union(self, other: Deck) -> Deck {
+        Deck(self.0 | other.0)
+    }
+
+    pub fn difference(self, other: Deck) -> Deck {
+        Deck(self.0 & !other.0)
+    }
+
+    pub fn from_mask(mask: u64) -> Self {
+        Deck(mask)
+    }
+
+    pub fn mask(&self) -> u64 {
+        self.0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Card> {
+        let mut mask = self.0;
+        std::iter::from_fn(move || {
+            let index = mask.trailing_zeros();
+            mask &= mask.checked_sub(1)?;
+            Some(Card::from_index(index))
+        })
+    }
+
     pub fn consume(self) -> impl Iterator<Item = Card> {
-        self.0.into_iter()
+        self.iter()
     }
 
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.0.count_ones() as usize
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.0 == 0
+    }
+
+    fn nth(&self, n: usize) -> Card {
+        let mut mask = self.0;
+        for _ in 0..n {
+            mask &= mask - 1;
+        }
+        Card::from_index(mask.trailing_zeros())
+    }
+}
+
+impl FromIterator<Card> for Deck {
+    fn from_iter<T: IntoIterator<Item = Card>>(cards: T) -> Self {
+        Deck(cards.into_iter().fold(0, |mask, card| mask | card.mask()))
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for Deck {
+    fn from_iter<T: IntoIterator<Item = &'a Card>>(cards: T) -> Self {
+        cards.into_iter().copied().collect()
     }
 }
 
 impl Default for Deck {
     fn default() -> Self {
-        Self(
-            Suit::ALL
-                .iter()
-                .copied()
-                .flat_map(|suit| {
-                    Rank::ALL
-                        .iter()
-                        .copied()
-                        .map(move |rank| Card { rank, suit })
-                })
-                .collect(),
-        )
+        Self((1 << (4 * RANKS)) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_default_deck() {
+        let deck = Deck::default();
+        assert_eq!(52, deck.len());
+        assert_eq!(52, deck.iter().unique().count());
+        assert_eq!(deck, deck.iter().collect());
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let deck = Deck::short();
+        assert_eq!(36, deck.len());
+        assert!(deck.contains(&parse_cards("6h")[0]));
+        assert!(!deck.contains(&parse_cards("5d")[0]));
+    }
+
+    #[test]
+    fn test_canonical_order() {
+        let cards = Deck::default().iter().collect_vec();
+        assert_eq!(parse_cards("2h")[0], cards[0]);
+        assert_eq!(parse_cards("Ah")[0], cards[12]);
+        assert_eq!(parse_cards("Ad")[0], cards[51]);
+        let deck = parse_cards("Kd 3c As").iter().collect::<Deck>();
+        assert_eq!(parse_cards("3c As Kd"), deck.iter().collect_vec());
+    }
+
+    #[test]
+    fn test_remove_and_insert() {
+        let mut deck = Deck::default();
+        let card = parse_cards("As")[0];
+        deck.remove(&card).unwrap();
+        assert!(!deck.contains(&card));
+        assert!(deck.remove(&card).is_err());
+        deck.insert(&card).unwrap();
+        assert!(deck.contains(&card));
+        assert!(deck.insert(&card).is_err());
+        assert_eq!(Deck::default(), deck);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let aces = parse_cards("Ah Ac As Ad").iter().collect::<Deck>();
+        let hearts = parse_cards("Ah Kh").iter().collect::<Deck>();
+        assert_eq!(5, aces.union(hearts).len());
+        assert_eq!(3, aces.difference(hearts).len());
+        assert_eq!(48, Deck::default().difference(aces).len());
+        assert_eq!(Deck::EMPTY, aces.difference(aces));
+    }
+
+    #[test]
+    fn test_deal() {
+        let mut rng = fastrand::Rng::with_seed(1);
+        let mut deck = Deck::default();
+        let dealt = deck.deal(7, &mut rng);
+        assert_eq!(7, dealt.iter().unique().count());
+        assert_eq!(45, deck.len());
+        assert!(dealt.iter().all(|card| !deck.contains(card)));
+
+        let mut again = Deck::default();
+        assert_eq!(dealt, again.deal(7, &mut fastrand::Rng::with_seed(1)));
     }
 }
@@ -1,5 +1,11 @@
+use crate::calc::StopReason;
 use crate::card::{Card, Rank, Suit};
+use crate::draw::Draw;
+use crate::eval::LowRank;
 use crate::hand::{Hand, HandType};
+use crate::preflop::StartingHand;
+use crate::texture::{Connectedness, HighCard, Pairing, Suits, Texture};
+use crate::variant::Variant;
 use std::fmt::{Display, Formatter};
 use HandType::*;
 use Rank::*;
@@ -137,3 +143,124 @@ impl Display for Suit {
         .fmt(fmt)
     }
 }
+
+impl Display for Variant {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Variant::Holdem => "Hold'em",
+            Variant::Omaha => "Omaha",
+            Variant::Omaha5 => "5-card Omaha",
+            Variant::OmahaHiLo => "Omaha Hi-Lo",
+            Variant::Omaha5HiLo => "5-card Omaha Hi-Lo",
+            Variant::Stud8 => "Seven Card Stud Hi-Lo",
+            Variant::ShortDeck => "Short-deck Hold'em",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for LowRank {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        let ranks = self
+            .ranks()
+            .map(|rank| rank.to_string())
+            .collect::<Vec<_>>();
+        write!(fmt, "{} low", ranks.join("-"))
+    }
+}
+
+impl Display for StopReason {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            StopReason::MaxSamples => "reached the maximum number of samples",
+            StopReason::TargetStandardError => "reached the target standard error",
+            StopReason::Deadline => "ran out of time",
+            StopReason::Cancelled => "cancelled",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for Draw {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Draw::FlushDraw => "Flush Draw",
+            Draw::BackdoorFlushDraw => "Backdoor Flush Draw",
+            Draw::OpenEnder => "Open-ended Straight Draw",
+            Draw::Gutshot => "Gutshot",
+            Draw::Overcards => "Overcards",
+            Draw::ComboDraw => "Combo Draw",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for Pairing {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Pairing::Unpaired => "unpaired",
+            Pairing::Paired => "paired",
+            Pairing::TwoPair => "double-paired",
+            Pairing::Trips => "trips",
+            Pairing::FullHouse => "full house",
+            Pairing::Quads => "quads",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for Suits {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Suits::Rainbow => "rainbow",
+            Suits::TwoTone => "two-tone",
+            Suits::ThreeFlush => "three-flush",
+            Suits::Monotone => "monotone",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for Connectedness {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Connectedness::Disconnected => "disconnected",
+            Connectedness::Gapped => "gapped",
+            Connectedness::Connected => "connected",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for HighCard {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            HighCard::Low => "low",
+            HighCard::Middle => "middling",
+            HighCard::Broadway => "broadway",
+            HighCard::Ace => "ace-high",
+        }
+        .fmt(fmt)
+    }
+}
+
+impl Display for Texture {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "{}, {}, {}, {}",
+            self.high_card, self.pairing, self.suits, self.connectedness
+        )
+    }
+}
+
+impl Display for StartingHand {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(fmt, "{}{}", self.high, self.low)?;
+        match (self.high == self.low, self.suited) {
+            (true, _) => Ok(()),
+            (false, true) => write!(fmt, "s"),
+            (false, false) => write!(fmt, "o"),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::card::{Card, Cards, Rank};
+use crate::variant::Variant;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::iter::once;
+use Draw::*;
+
+/// A way a hold'em hand could improve on the turn or the river.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Draw {
+    FlushDraw,
+    /// Needs both the turn and the river.
+    BackdoorFlushDraw,
+    /// Two ranks would complete a straight, e.g. 8-9-T-J or a double gutshot.
+    OpenEnder,
+    Gutshot,
+    /// Both hole cards outrank every card on the board.
+    Overcards,
+    ComboDraw,
+}
+
+impl Draw {
+    pub const ALL: [Draw; 6] = [
+        FlushDraw,
+        BackdoorFlushDraw,
+        OpenEnder,
+        Gutshot,
+        Overcards,
+        ComboDraw,
+    ];
+}
+
+/// The draws a player has on the flop or the turn, or `None` in variants other than hold'em and
+/// short-deck hold'em.
+pub fn draws(variant: Variant, hole_cards: &Cards, board: &Cards) -> Option<Vec<Draw>> {
+    let lowest = match variant {
+        Variant::Holdem => Rank::Deuce,
+        Variant::ShortDeck => Rank::Six,
+        _ => return None,
+    };
+    if !(3..5).contains(&board.len()) {
+        return Some(vec![]);
+    }
+    let mut draws = vec![];
+    let cards = [hole_cards, board].concat();
+    let suited = |cards: &[Card], suit| cards.iter().filter(|card| card.suit == suit).count();
+    let flushes = hole_cards
+        .iter()
+        .map(|card| card.suit)
+        .unique()
+        .map(|suit| suited(&cards, suit))
+        .collect_vec();
+    if !flushes.iter().any(|n| *n >= 5) {
+        if flushes.contains(&4) {
+            draws.push(FlushDraw);
+        } else if flushes.contains(&3) && board.len() == 3 {
+            draws.push(BackdoorFlushDraw);
+        }
+    }
+    let (ranks, board_ranks) = (ranks(&cards), ranks(board));
+    if best_straight(ranks, lowest).is_none() {
+        // ranks that would make a straight using at least one hole card
+        let outs = (lowest as u32..=Rank::Ace as u32)
+            .map(|rank| 1 << (rank - Rank::Deuce as u32))
+            .filter(|rank| ranks & rank == 0)
+            .filter(|rank| {
+                best_straight(ranks | rank, lowest) > best_straight(board_ranks | rank, lowest)
+            })
+            .count();
+        match outs {
+            0 => {}
+            1 => draws.push(Gutshot),
+            _ => draws.push(OpenEnder),
+        }
+    }
+    let top = board.iter().map(|card| card.rank).max();
+    if hole_cards.iter().map(|card| card.rank).all_unique()
+        && hole_cards.iter().all(|card| Some(card.rank) > top)
+    {
+        draws.push(Overcards);
+    }
+    if draws.contains(&FlushDraw) && (draws.contains(&OpenEnder) || draws.contains(&Gutshot)) {
+        draws.push(ComboDraw);
+    }
+    Some(draws)
+}
+
+fn ranks(cards: &Cards) -> u16 {
+    cards.iter().fold(0, |ranks, card| {
+        ranks | 1 << (card.rank as u32 - Rank::Deuce as u32)
+    })
+}
+
+fn best_straight(ranks: u16, lowest: Rank) -> Option<usize> {
+    let low = lowest as u32 - Rank::Deuce as u32;
+    let wheel = 1 << 12 | 0b1111 << low;
+    once(wheel)
+        .chain((low..=8).map(|i| 0b11111 << i))
+        .positions(|straight| ranks & straight == straight)
+        .last()
+}
+
+/// How often each draw is held across the combos of a range.
+#[derive(Clone, Debug, Default)]
+pub struct DrawDistribution {
+    weights: HashMap<Draw, f64>,
+    total: f64,
+}
+
+impl DrawDistribution {
+    pub fn update(mut self, draws: &[Draw], weight: f64) -> Self {
+        for draw in draws {
+            *self.weights.entry(*draw).or_insert(0.0) += weight;
+        }
+        self.total += weight;
+        self
+    }
+
+    /// The percentage of combos holding each draw, leaving out draws nobody holds.
+    pub fn percentages(&self) -> impl Iterator<Item = (Draw, f64)> + '_ {
+        Draw::ALL.into_iter().filter_map(|draw| {
+            let weight = self.weights.get(&draw)?;
+            Some((draw, 100.0 * weight / self.total))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn draws(hole_cards: &str, board: &str) -> Vec<Draw> {
+        super::draws(
+            Variant::Holdem,
+            &parse_cards(hole_cards),
+            &parse_cards(board),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flush_draws() {
+        assert_eq!(vec![FlushDraw], draws("Ah 5h", "Kh 9h 2c"));
+        assert_eq!(vec![BackdoorFlushDraw], draws("Ah 5h", "Kh 9c 2c"));
+        assert_eq!(Vec::<Draw>::new(), draws("Ah 5h", "Kh 9c 2c 8d"));
+        // four hearts on board aren't a draw without a heart in hand
+        assert_eq!(Vec::<Draw>::new(), draws("Ac 5d", "Kh 9h 2h Th"));
+    }
+
+    #[test]
+    fn test_straight_draws() {
+        assert_eq!(vec![OpenEnder], draws("8c 9d", "Th Js 2c"));
+        assert_eq!(vec![Gutshot], draws("8c 9d", "Th Qs 2c"));
+        // double gutshot
+        assert_eq!(vec![OpenEnder], draws("9c Jd", "7h Ks Tc"));
+        // only a ten: there's nothing above an ace
+        assert_eq!(vec![Gutshot, Overcards], draws("Ac Kd", "Qh Js 2c"));
+        // only a deuce, for the wheel
+        assert_eq!(vec![Gutshot], draws("Ac 3d", "4h 5s Kc"));
+        // four to a straight on board
+        assert_eq!(Vec::<Draw>::new(), draws("2c 2d", "5h 6s 7c 8d"));
+    }
+
+    #[test]
+    fn test_combo_draw() {
+        assert_eq!(
+            vec![FlushDraw, OpenEnder, ComboDraw],
+            draws("8h 9h", "Th Jh 2c")
+        );
+    }
+
+    #[test]
+    fn test_short_deck_straight_draws() {
+        // A-6-7-8-9 is the lowest straight in short deck
+        let draws = super::draws(
+            Variant::ShortDeck,
+            &parse_cards("Ac 6d"),
+            &parse_cards("7h 8s Kc"),
+        );
+        assert_eq!(Some(vec![Gutshot]), draws);
+        assert_eq!(Vec::<Draw>::new(), self::draws("Ac 6d", "7h 8s Kc"));
+    }
+
+    #[test]
+    fn test_unsupported_variants() {
+        let hole_cards = parse_cards("Ah 5h 8c 9d");
+        let draws = super::draws(Variant::Omaha, &hole_cards, &parse_cards("Kh 9h Tc"));
+        assert_eq!(None, draws);
+    }
+
+    #[test]
+    fn test_draw_distribution() {
+        let distribution = DrawDistribution::default()
+            .update(&[FlushDraw], 1.0)
+            .update(&[FlushDraw, Gutshot, ComboDraw], 0.5)
+            .update(&[], 0.5);
+        assert_eq!(
+            vec![(FlushDraw, 75.0), (Gutshot, 25.0), (ComboDraw, 25.0)],
+            distribution.percentages().collect_vec()
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::calc::HandOdds;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// What's at stake for a player facing a bet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stakes {
+    /// The pot, including the bet faced.
+    pot: f64,
+    to_call: f64,
+    /// The smaller of the player's stack and the bettor's, if they can't cover the bet.
+    stack: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StakesError {
+    InvalidAmount(f64),
+    CallExceedsPot,
+}
+impl Display for StakesError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            StakesError::InvalidAmount(amount) => write!(fmt, "invalid amount: {}", amount),
+            StakesError::CallExceedsPot => {
+                write!(
+                    fmt,
+                    "the amount to call must not exceed the pot, which includes it"
+                )
+            }
+        }
+    }
+}
+impl Error for StakesError {}
+
+/// The expected value of calling a bet, and what it takes for calling or bluffing to pay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ev {
+    /// The chips the player wins on average by calling rather than folding.
+    pub call: f64,
+    /// The equity, in percent, at which calling breaks even, i.e. the pot odds.
+    pub required_equity: f64,
+    /// How often, in percent, a bluff this size must work to break even.
+    pub bluff_fold_frequency: f64,
+}
+
+impl Stakes {
+    /// A bet of `to_call` into a `pot` that already includes it.
+    pub fn new(pot: f64, to_call: f64, stack: Option<f64>) -> Result<Self, StakesError> {
+        for amount in [pot, to_call].into_iter().chain(stack) {
+            if !(amount.is_finite() && amount >= 0.0) {
+                return Err(StakesError::InvalidAmount(amount));
+            }
+        }
+        if to_call > pot {
+            return Err(StakesError::CallExceedsPot);
+        }
+        Ok(Self {
+            pot,
+            to_call,
+            stack,
+        })
+    }
+
+    /// The expected value of calling with `equity`, a share of the pot between 0 and 1.
+    pub fn ev(&self, equity: f64) -> Ev {
+        // a player who can't cover the bet calls all in, and the rest of the bet is returned
+        let call = self
+            .stack
+            .map_or(self.to_call, |stack| stack.min(self.to_call));
+        let pot = self.pot - (self.to_call - call);
+        let ratio = |amount: f64, total: f64| match total {
+            total if total > 0.0 => 100.0 * amount / total,
+            _ => 0.0,
+        };
+        Ev {
+            call: equity * (pot + call) - call,
+            required_equity: ratio(call, pot + call),
+            bluff_fold_frequency: ratio(call, pot),
+        }
+    }
+}
+
+impl HandOdds {
+    pub fn ev(&self, stakes: Stakes) -> Ev {
+        stakes.ev(self.equity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_ev() {
+        // a pot-sized bet into 50
+        let ev = Stakes::new(100.0, 50.0, None).unwrap().ev(0.5);
+        assert!(close(25.0, ev.call));
+        assert!(close(100.0 / 3.0, ev.required_equity));
+        assert!(close(50.0, ev.bluff_fold_frequency));
+        assert!(close(
+            0.0,
+            Stakes::new(100.0, 50.0, None).unwrap().ev(1.0 / 3.0).call
+        ));
+
+        // all in for 20, with the other 30 returned
+        let ev = Stakes::new(100.0, 50.0, Some(20.0)).unwrap().ev(0.5);
+        assert!(close(25.0, ev.call));
+        assert!(close(200.0 / 9.0, ev.required_equity));
+        assert!(close(200.0 / 7.0, ev.bluff_fold_frequency));
+
+        let check = Stakes::new(0.0, 0.0, None).unwrap().ev(0.5);
+        assert_eq!(0.0, check.required_equity);
+    }
+
+    #[test]
+    fn test_invalid_stakes() {
+        assert_eq!(
+            Err(StakesError::CallExceedsPot),
+            Stakes::new(10.0, 20.0, None)
+        );
+        assert_eq!(
+            Err(StakesError::InvalidAmount(-1.0)),
+            Stakes::new(10.0, 5.0, Some(-1.0))
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use crate::card::{Card, Rank, RANKS};
+use crate::hand::{Hand, HandType};
+use std::sync::OnceLock;
+use HandType::*;
+
+const RANK_MASK: u64 = (1 << RANKS) - 1;
+
+/// The strength of a hand: the position of its type in the top four bits, then its ranks.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandRank(u16);
+
+const HAND_TYPES: [HandType; 10] = [
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    Flush,
+    FourOfAKind,
+    StraightFlush,
+];
+
+/// The position of each hand type, leaving room for a short-deck flush.
+const POSITIONS: [u16; 9] = [0, 1, 2, 3, 4, 5, 6, 8, 9];
+
+const SHORT_DECK_FLUSH: u16 = 7;
+
+impl HandRank {
+    fn new(hand_type: HandType, index: u16) -> Self {
+        Self(POSITIONS[hand_type as usize] << 12 | index)
+    }
+
+    pub fn hand_type(self) -> HandType {
+        HAND_TYPES[(self.0 >> 12) as usize]
+    }
+
+    /// The `Hand` with this rank that can be made from the given cards, for display.
+    pub fn hand(self, cards: u64) -> Hand {
+        let tables = tables();
+        let rank = self.0 & 0xfff;
+        let mut cards = match self.hand_type() {
+            StraightFlush | Flush => {
+                let suit = (0..4)
+                    .find(|suit| ((cards >> (suit * RANKS)) & RANK_MASK).count_ones() >= 5)
+                    .unwrap();
+                cards & (RANK_MASK << (suit * RANKS))
+            }
+            _ => cards,
+        };
+        let groups: Vec<(u16, usize)> = match self.hand_type() {
+            HighCard | Flush => ranks_of(tables.unrank[5][rank as usize])
+                .map(|r| (r, 1))
+                .collect(),
+            Pair => {
+                let (pair, kickers) = (rank / 286, rank % 286);
+                let kickers = ranks_of(tables.unrank[3][kickers as usize]).map(|r| (r, 1));
+                [(pair, 2)].into_iter().chain(kickers).collect()
+            }
+            TwoPair => {
+                let (pairs, kicker) = (rank / 13, rank % 13);
+                let pairs = ranks_of(tables.unrank[2][pairs as usize]).map(|r| (r, 2));
+                pairs.chain([(kicker, 1)]).collect()
+            }
+            ThreeOfAKind => {
+                let (trips, kickers) = (rank / 78, rank % 78);
+                let kickers = ranks_of(tables.unrank[2][kickers as usize]).map(|r| (r, 1));
+                [(trips, 3)].into_iter().chain(kickers).collect()
+            }
+            Straight | StraightFlush => {
+                let present =
+                    (0..4).fold(0, |ranks, suit| ranks | cards >> (suit * RANKS) & RANK_MASK);
+                (0..5)
+                    .map(|i| (rank + RANKS as u16 - i) % RANKS as u16)
+                    // in short deck's A-6-7-8-9, the ace takes the missing five's place
+                    .map(|r| match present & (1 << r) {
+                        0 => (RANKS as u16 - 1, 1),
+                        _ => (r, 1),
+                    })
+                    .collect()
+            }
+            FullHouse => vec![(rank / 13, 3), (rank % 13, 2)],
+            FourOfAKind => vec![(rank / 13, 4), (rank % 13, 1)],
+        };
+        let mut hand = Vec::with_capacity(Hand::HAND_SIZE);
+        for (rank, count) in groups {
+            for _ in 0..count {
+                let suit = (0..4)
+                    .find(|suit| cards & (1 << (suit * RANKS + rank as u32)) != 0)
+                    .unwrap();
+                let bit = 1 << (suit * RANKS + rank as u32);
+                cards &= !bit;
+                hand.push(Card::from_index(bit.trailing_zeros()));
+            }
+        }
+        Hand {
+            hand_type: self.hand_type(),
+            cards: hand.try_into().unwrap(),
+        }
+    }
+}
+
+pub fn evaluate(cards: u64) -> HandRank {
+    evaluate_with::<false>(cards)
+}
+
+/// Like `evaluate`, but the ace also plays below the six and a flush beats a full house.
+pub fn evaluate_short_deck(cards: u64) -> HandRank {
+    let rank = evaluate_with::<true>(cards);
+    match rank.hand_type() {
+        Flush => HandRank(SHORT_DECK_FLUSH << 12 | rank.0 & 0xfff),
+        _ => rank,
+    }
+}
+
+fn evaluate_with<const SHORT_DECK: bool>(cards: u64) -> HandRank {
+    let tables = tables();
+    // with the deuce to five removed, the ace can take the five's place in A-6-7-8-9
+    let straight = |ranks: usize| match SHORT_DECK {
+        true => tables.straight[ranks | (ranks >> (RANKS - 1)) << 3],
+        false => tables.straight[ranks],
+    };
+    let [s0, s1, s2, s3] =
+        [0, 1, 2, 3].map(|suit| ((cards >> (suit * RANKS)) & RANK_MASK) as usize);
+    for suit in [s0, s1, s2, s3] {
+        if suit.count_ones() >= 5 {
+            return match straight(suit) {
+                0 => HandRank::new(Flush, tables.colex[5][suit]),
+                high => HandRank::new(StraightFlush, high as u16 - 1),
+            };
+        }
+    }
+    let ranks = s0 | s1 | s2 | s3;
+    let quads = s0 & s1 & s2 & s3;
+    let three = (s0 & s1 & s2) | (s0 & s1 & s3) | (s0 & s2 & s3) | (s1 & s2 & s3);
+    let two = (s0 & s1) | (s0 & s2) | (s0 & s3) | (s1 & s2) | (s1 & s3) | (s2 & s3);
+    let trips = three & !quads;
+    let pairs = two & !three;
+    if quads != 0 {
+        let quad = highest(quads);
+        return HandRank::new(FourOfAKind, quad * 13 + highest(ranks & !(1 << quad)));
+    }
+    if trips != 0 {
+        let trip = highest(trips);
+        let rest = (trips & !(1 << trip)) | pairs;
+        if rest != 0 {
+            return HandRank::new(FullHouse, trip * 13 + highest(rest));
+        }
+    }
+    if straight(ranks) != 0 {
+        return HandRank::new(Straight, straight(ranks) as u16 - 1);
+    }
+    if trips != 0 {
+        let trip = highest(trips);
+        return HandRank::new(
+            ThreeOfAKind,
+            trip * 78 + tables.colex[2][ranks & !(1 << trip)],
+        );
+    }
+    if pairs.count_ones() >= 2 {
+        let top = tables.top[2][pairs];
+        return HandRank::new(TwoPair, tables.colex[2][pairs] * 13 + highest(ranks & !top));
+    }
+    if pairs != 0 {
+        let pair = highest(pairs);
+        return HandRank::new(Pair, pair * 286 + tables.colex[3][ranks & !pairs]);
+    }
+    HandRank::new(HighCard, tables.colex[5][ranks])
+}
+
+/// An eight-or-better low; greater is better.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LowRank(u8);
+
+impl LowRank {
+    pub fn ranks(self) -> impl Iterator<Item = Rank> {
+        let ranks = !self.0;
+        (0..8)
+            .rev()
+            .filter(move |bit| ranks & (1 << bit) != 0)
+            .map(|bit| match bit {
+                0 => Rank::Ace,
+                bit => Rank::ALL[bit - 1],
+            })
+    }
+}
+
+/// The best eight-or-better low that can be made from the cards, if any.
+pub fn evaluate_low(cards: u64) -> Option<LowRank> {
+    let ranks = (0..4).fold(0, |ranks, suit| {
+        ranks | (cards >> (suit * RANKS)) & RANK_MASK
+    });
+    // deuce to eight move up one bit to make room for the ace at the bottom
+    let mut low = ((ranks & 0x7f) << 1 | ranks >> (RANKS - 1)) as u8;
+    if low.count_ones() < 5 {
+        return None;
+    }
+    while low.count_ones() > 5 {
+        low &= !(1 << (7 - low.leading_zeros()));
+    }
+    Some(LowRank(!low))
+}
+
+fn highest(ranks: usize) -> u16 {
+    (usize::BITS - 1).saturating_sub(ranks.leading_zeros()) as u16
+}
+
+fn ranks_of(ranks: usize) -> impl Iterator<Item = u16> {
+    (0..RANKS as u16)
+        .rev()
+        .filter(move |rank| ranks & (1 << rank) != 0)
+}
+
+struct Tables {
+    /// One more than the index of the highest card of the best straight, or zero if none.
+    straight: Vec<u8>,
+    top: [Vec<usize>; 6],
+    /// The position of the top `k` ranks in colexicographic order, which orders kickers.
+    colex: [Vec<u16>; 6],
+    unrank: [Vec<usize>; 6],
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let masks = 0..1usize << RANKS;
+        let straight = masks
+            .clone()
+            .map(|ranks| {
+                // the ace also plays low, below the deuce
+                let ranks = ranks << 1 | ranks >> (RANKS - 1);
+                (4..=RANKS as usize)
+                    .rev()
+                    .find(|high| (ranks >> (high - 4)) & 0b11111 == 0b11111)
+                    .map_or(0, |high| high as u8)
+            })
+            .collect();
+        let top = [0, 1, 2, 3, 4, 5].map(|k| {
+            masks
+                .clone()
+                .map(|ranks| ranks_of(ranks).take(k).fold(0, |top, rank| top | 1 << rank))
+                .collect::<Vec<_>>()
+        });
+        let colex = [0, 1, 2, 3, 4, 5].map(|k| {
+            masks
+                .clone()
+                .map(|ranks| {
+                    ranks_of(ranks)
+                        .take(k)
+                        .enumerate()
+                        .map(|(i, rank)| choose(rank as usize, k - i))
+                        .sum()
+                })
+                .collect::<Vec<_>>()
+        });
+        let unrank = [0, 1, 2, 3, 4, 5].map(|k| {
+            let mut unrank = vec![0; choose(RANKS as usize, k) as usize];
+            for ranks in masks
+                .clone()
+                .filter(|ranks| ranks.count_ones() as usize == k)
+            {
+                unrank[colex[k][ranks] as usize] = ranks;
+            }
+            unrank
+        });
+        Tables {
+            straight,
+            top,
+            colex,
+            unrank,
+        }
+    })
+}
+
+fn choose(n: usize, k: usize) -> u16 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1)) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::HandTypeDistribution;
+    use crate::card::{mask, parse_cards, Card};
+    use crate::hand::hand;
+    use crate::Deck;
+    use itertools::Itertools;
+
+    fn random_cards(rng: &mut fastrand::Rng, n: usize) -> Vec<Card> {
+        let mut deck = Deck::default().consume().collect_vec();
+        rng.shuffle(&mut deck);
+        deck.truncate(n);
+        deck
+    }
+
+    #[test]
+    fn test_handles_all_valid_hands() {
+        let mut distribution = HandTypeDistribution::default();
+        for a in 6..52 {
+            for b in 5..a {
+                for c in 4..b {
+                    for d in 3..c {
+                        let four = 1 << a | 1 << b | 1 << c | 1 << d;
+                        for e in 2..d {
+                            for f in 1..e {
+                                for g in 0..f {
+                                    let cards = four | 1 << e | 1 << f | 1 << g;
+                                    distribution = distribution.update(evaluate(cards).hand_type());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        assert_eq!(41_584, distribution.frequency_of(&StraightFlush));
+        assert_eq!(224_848, distribution.frequency_of(&FourOfAKind));
+        assert_eq!(3_473_184, distribution.frequency_of(&FullHouse));
+        assert_eq!(4_047_644, distribution.frequency_of(&Flush));
+        assert_eq!(6_180_020, distribution.frequency_of(&Straight));
+        assert_eq!(6_461_620, distribution.frequency_of(&ThreeOfAKind));
+        assert_eq!(31_433_400, distribution.frequency_of(&TwoPair));
+        assert_eq!(58_627_800, distribution.frequency_of(&Pair));
+        assert_eq!(23_294_460, distribution.frequency_of(&HighCard));
+    }
+
+    #[test]
+    fn test_distinct_five_card_hands() {
+        let ranks = Deck::default()
+            .consume()
+            .combinations(5)
+            .map(|cards| evaluate(mask(&cards)))
+            .unique()
+            .count();
+        assert_eq!(7462, ranks);
+    }
+
+    #[test]
+    fn test_agrees_with_hand() {
+        let mut rng = fastrand::Rng::with_seed(1);
+        for _ in 0..100_000 {
+            let cards1 = random_cards(&mut rng, 7);
+            let cards2 = random_cards(&mut rng, 7);
+            let (rank1, rank2) = (evaluate(mask(&cards1)), evaluate(mask(&cards2)));
+            let (hand1, hand2) = (hand(cards1.clone()), hand(cards2.clone()));
+            assert_eq!(hand1.hand_type, rank1.hand_type(), "{:?}", cards1);
+            assert_eq!(
+                hand1.cmp(&hand2),
+                rank1.cmp(&rank2),
+                "{:?} {:?}",
+                cards1,
+                cards2
+            );
+            assert_eq!(hand1, rank1.hand(mask(&cards1)), "{:?}", cards1);
+        }
+    }
+
+    #[test]
+    fn test_wheel() {
+        let wheel = evaluate(mask(&parse_cards("As 2d 3h 4c 5s 9d Kh")));
+        let six_high = evaluate(mask(&parse_cards("6s 2d 3h 4c 5s 9d Kh")));
+        assert_eq!(Straight, wheel.hand_type());
+        assert!(wheel < six_high);
+        let cards = parse_cards("As 2s 3s 4s 5s 9d Kh");
+        let hand = evaluate(mask(&cards)).hand(mask(&cards));
+        assert_eq!(StraightFlush, hand.hand_type);
+        assert_eq!(parse_cards("5s 4s 3s 2s As"), hand.cards.to_vec());
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let short = |cards| evaluate_short_deck(mask(&parse_cards(cards)));
+        let wheel = short("As 6d 7h 8c 9s Kd Kh");
+        assert_eq!(Straight, wheel.hand_type());
+        assert!(wheel < short("Ts 6d 7h 8c 9s Kd Kh"));
+        assert!(wheel > short("As Ad Ah 8c 9s Kd Qh"));
+        let flush = short("As 6s 7s 8s Ts Kd Kh");
+        let full_house = short("As Ad Ah 8c 8s Kd Qh");
+        assert!(flush > full_house);
+        assert!(flush < short("As Ad Ah Ac 8s Kd Qh"));
+        assert_eq!(
+            (Flush, FullHouse),
+            (flush.hand_type(), full_house.hand_type())
+        );
+        assert!(evaluate(mask(&parse_cards("As 6s 7s 8s Ts Kd Kh"))) < full_house);
+        assert!(short("As 6s 7s 8s 9s Kd Kh") > short("Ac Ad Ah As 9s Kd Kh"));
+        assert_eq!(StraightFlush, short("As 6s 7s 8s 9s Kd Kh").hand_type());
+    }
+
+    #[test]
+    fn test_eight_or_better() {
+        let low = |cards| evaluate_low(mask(&parse_cards(cards)));
+        assert_eq!(None, low("9s 9d Ah 2c 3d 4h Kc"));
+        assert_eq!(None, low("As Ad 2h 2c 3d 4h 4c"));
+        let wheel = low("As Ad 2h 3c 4d 5h Kc").unwrap();
+        let seven_five = low("7s 5d 4h 3c 2d Kh Kc").unwrap();
+        let seven_six = low("7s 6d 3h 2c Ad 8h Kc").unwrap();
+        let eight = low("8s 7d 6h 5c 4d").unwrap();
+        assert!(wheel > seven_five && seven_five > seven_six && seven_six > eight);
+        assert_eq!(
+            vec![Rank::Five, Rank::Four, Rank::Trey, Rank::Deuce, Rank::Ace],
+            wheel.ranks().collect_vec()
+        );
+        let lows = Deck::default()
+            .consume()
+            .combinations(5)
+            .filter_map(|cards| evaluate_low(mask(&cards)))
+            .unique()
+            .count();
+        assert_eq!(56, lows);
+    }
+
+    #[test]
+    fn test_hand_for_display() {
+        let cards = parse_cards("8h 8s 8c Ah As 9c Ac");
+        let hand = evaluate(mask(&cards)).hand(mask(&cards));
+        assert_eq!(FullHouse, hand.hand_type);
+        assert_eq!(parse_cards("Ah Ac As 8h 8c"), hand.cards.to_vec());
+    }
+}
@@ -4,11 +4,21 @@ pub trait Rng<T> {
     fn generate(&mut self, range: impl std::ops::RangeBounds<T>) -> T;
 }
 
-pub fn permutations<T: Clone>(
-    k: usize,
-    deck: Vec<T>,
-    mut rng: impl Rng<usize>,
-) -> impl Iterator<Item = Vec<T>> {
+/// A random number generator that splits into independent streams, for reproducible parallel work.
+pub trait SplitRng: Rng<usize> + Sized {
+    /// Depends only on the seed and `index`.
+    fn stream(&self, index: u64) -> Self;
+}
+
+/// Derives the `index`th stream's seed with the SplitMix64 finalizer.
+pub fn split_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+pub fn sample(k: usize, n: usize, rng: &mut impl Rng<usize>) -> VecDeque<usize> {
     // Robert Floyd's Algorithm: sample a single random permutation
     // https://dl.acm.org/doi/pdf/10.1145/30401.315746
     //
@@ -20,20 +30,16 @@ pub fn permutations<T: Clone>(
     //   else
     //     insert J in S after T
     let mut result = VecDeque::with_capacity(k);
-    let n = deck.len();
-    std::iter::from_fn(move || {
-        result.clear();
-        for j in (n - k)..n {
-            let t = rng.generate(0..=j);
-            if let Some(i) = result.iter().position(|x| *x == t) {
-                result.insert(i + 1, j);
-            } else {
-                result.push_front(t);
-            }
+    for j in (n - k)..n {
+        let t = rng.generate(0..=j);
+        if let Some(i) = result.iter().position(|x| *x == t) {
+            result.insert(i + 1, j);
+        } else {
+            result.push_front(t);
         }
-        assert_eq!(k, result.len());
-        Some(result.iter().map(|i| deck[*i].clone()).collect())
-    })
+    }
+    assert_eq!(k, result.len());
+    result
 }
 
 #[cfg(test)]
@@ -41,12 +47,31 @@ mod tests {
     use super::*;
     use itertools::Itertools;
 
+    fn permutations<T: Clone>(
+        k: usize,
+        deck: Vec<T>,
+        mut rng: impl Rng<usize>,
+    ) -> impl Iterator<Item = Vec<T>> {
+        std::iter::repeat_with(move || {
+            sample(k, deck.len(), &mut rng)
+                .into_iter()
+                .map(|i| deck[i].clone())
+                .collect()
+        })
+    }
+
     impl Rng<usize> for fastrand::Rng {
         fn generate(&mut self, range: impl std::ops::RangeBounds<usize>) -> usize {
             self.usize(range)
         }
     }
 
+    impl SplitRng for fastrand::Rng {
+        fn stream(&self, index: u64) -> Self {
+            fastrand::Rng::with_seed(split_seed(self.get_seed(), index))
+        }
+    }
+
     fn mean(data: &[usize]) -> f64 {
         // rust cookbook
         let sum = data.iter().sum::<usize>() as f64;
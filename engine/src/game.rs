@@ -1,15 +1,48 @@
-use crate::calc::{odds, outcomes, HandOutcome, Odds};
-use crate::card::{Card, HoleCards};
+use crate::calc::{
+    exhaustive_odds_observed, odds_until, outcomes, scenarios, side_pot_shares, HandOutcome, Odds,
+    Spot, StopReason, StoppingRule, EXHAUSTIVE_THRESHOLD,
+};
+use crate::card::{mask, Card, HoleCards};
 use crate::deck::{Deck, DeckError};
-use crate::Rng;
-
-const BOARD_LENGTH: usize = 5;
+use crate::draw::{draws, DrawDistribution};
+use crate::nuts::{Holding, Holdings};
+use crate::outs::{outs, Out};
+use crate::pots::{side_pots, SidePot};
+use crate::preflop::PreflopTable;
+use crate::range::Range;
+use crate::suits::{canonical, encode, SuitPermutation};
+use crate::texture::{texture, Texture};
+use crate::variant::Variant;
+use crate::SplitRng;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::iter::zip;
+use std::ops::ControlFlow;
+use std::sync::Arc;
 
 pub struct Game {
-    players: Vec<HoleCards>,
+    variant: Variant,
+    players: Vec<Range>,
     board: Vec<Card>,
     opponents: usize,
+    /// Opponents dealt from a range rather than at random.
+    opponent_ranges: Vec<Range>,
+    n_folded: usize,
+    streets: bool,
+    preflop_table: Option<Arc<PreflopTable>>,
+    contributions: Vec<f64>,
+}
+
+/// Identifies a game, e.g. to cache its odds.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameKey {
+    variant: Variant,
+    cards: Vec<u64>,
+    opponents: usize,
+    opponent_ranges: usize,
     n_folded: usize,
+    streets: bool,
+    contributions: Vec<u64>,
 }
 
 pub enum GameState {
@@ -17,63 +50,811 @@ pub enum GameState {
     Undecided(Odds),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Exhaustive,
+    MonteCarlo,
+    Lookup,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    ImprovesHero,
+    ImprovesVillain,
+    Neutral,
+}
+
+/// The smallest change in the hero's equity, in percentage points, that isn't neutral.
+pub const NEUTRAL_MARGIN: f64 = 1.0;
+
+pub struct NextCard {
+    pub card: Card,
+    pub equity: Vec<f64>,
+    pub effect: Effect,
+}
+
+pub struct NextCards {
+    pub equity: Vec<f64>,
+    pub cards: Vec<NextCard>,
+}
+
+pub struct Nuts {
+    pub top: Vec<Holding>,
+    /// The percentage of holdings each known player beats, or `None` for a range.
+    pub strength: Vec<Option<f64>>,
+}
+
+impl GameState {
+    /// Known players first, then any unknown opponents merged.
+    pub fn equity_percent(self, n_players: usize) -> Vec<f64> {
+        match self {
+            GameState::GameOver(outcomes) => outcomes
+                .into_iter()
+                .map(|outcome| 100.0 * outcome.share)
+                .collect(),
+            GameState::Undecided(odds) => odds
+                .merge_unknown_players(n_players)
+                .into_iter()
+                .map(|odds| odds.equity_percent())
+                .collect(),
+        }
+    }
+}
+
 pub struct GameOutcome {
     pub state: GameState,
     pub cards_remaining: usize,
+    pub method: Method,
+    /// The combos each known player could still hold, by weight.
+    pub live_combos: Vec<f64>,
+    pub samples: u64,
+    pub stop_reason: Option<StopReason>,
+    pub pots: Option<Pots>,
+}
+
+pub struct Pots {
+    pub pots: Vec<SidePot>,
+    /// As percentages, known players first; ineligible players get nothing.
+    pub equity: Vec<Vec<f64>>,
+    /// The chips each player wins on average, less those they put in.
+    pub chip_ev: Vec<f64>,
 }
 
 impl Game {
-    pub fn new(
-        players: Vec<HoleCards>,
-        board: Vec<Card>,
-        opponents: usize,
-        n_folded: usize,
-    ) -> Self {
+    pub fn new(players: Vec<Range>, board: Vec<Card>, opponents: usize, n_folded: usize) -> Self {
         Game {
+            variant: Variant::default(),
             players,
             board,
             opponents,
+            opponent_ranges: vec![],
             n_folded,
+            streets: false,
+            preflop_table: None,
+            contributions: vec![],
+        }
+    }
+
+    pub fn with_variant(self, variant: Variant) -> Self {
+        Game { variant, ..self }
+    }
+
+    /// Ranged opponents come before any opponents dealt at random.
+    pub fn with_opponent_ranges(self, opponent_ranges: Vec<Range>) -> Self {
+        Game {
+            opponent_ranges,
+            ..self
+        }
+    }
+
+    pub fn with_streets(self, streets: bool) -> Self {
+        Game { streets, ..self }
+    }
+
+    pub fn with_preflop_table(self, table: Arc<PreflopTable>) -> Self {
+        Game {
+            preflop_table: Some(table),
+            ..self
+        }
+    }
+
+    /// Known players first, then ranged opponents, then any other opponents.
+    pub fn with_contributions(self, contributions: Vec<f64>) -> Self {
+        Game {
+            contributions,
+            ..self
+        }
+    }
+
+    /// Relabels the suits so that games differing only by suits become the same game.
+    pub fn canonical(&self) -> (Game, SuitPermutation) {
+        let (mut players, board, permutation) = canonical(&self.ranges(), &self.board);
+        let opponent_ranges = players.split_off(self.players.len());
+        let game = Game {
+            players,
+            opponent_ranges,
+            board,
+            preflop_table: self.preflop_table.clone(),
+            contributions: self.contributions.clone(),
+            ..*self
+        };
+        (game, permutation)
+    }
+
+    pub fn key(&self) -> GameKey {
+        GameKey {
+            variant: self.variant,
+            cards: encode(&self.ranges(), &self.board),
+            opponents: self.opponents,
+            opponent_ranges: self.opponent_ranges.len(),
+            n_folded: self.n_folded,
+            streets: self.streets,
+            contributions: self.contributions.iter().map(|c| c.to_bits()).collect(),
         }
     }
 
     pub fn play(
         &self,
-        rng: impl Rng<usize> + Send,
-        permutations: usize,
-    ) -> Result<GameOutcome, DeckError> {
-        let mut deck = Deck::default();
-        for player in &self.players {
-            for card in player {
-                deck.remove(card)?;
-            }
+        rng: impl SplitRng + Sync,
+        rule: StoppingRule,
+    ) -> Result<GameOutcome, GameError> {
+        self.play_observed(rng, rule, |_, _, _| ControlFlow::Continue(()))
+    }
+
+    /// Like `play`, but `observer` is shown the odds so far, the scenarios evaluated or sampled and
+    /// how many there will be at most, and can break to stop early.
+    pub fn play_observed(
+        &self,
+        rng: impl SplitRng + Sync,
+        rule: StoppingRule,
+        observer: impl FnMut(&Odds, usize, usize) -> ControlFlow<()>,
+    ) -> Result<GameOutcome, GameError> {
+        let deck = self.deck()?;
+        let players = self.live_players(&deck);
+        if let Some(i) = players.iter().position(Range::is_empty) {
+            return Err(GameError::EmptyRange(i + 1));
         }
-        for card in &self.board {
-            deck.remove(card)?;
+        if !is_feasible(&players, &mut vec![]) {
+            return Err(GameError::ConflictingRanges);
         }
+        let pots = self.side_pots()?;
         let cards_remaining = deck.len();
-        Ok(if self.is_over() {
-            GameOutcome {
-                state: GameState::GameOver(outcomes(&self.players, &self.board).collect()),
-                cards_remaining,
-            }
+        let live_combos = players[..self.players.len()]
+            .iter()
+            .map(Range::weight)
+            .collect();
+        let spot = Spot {
+            variant: self.variant,
+            players: &players,
+            board: &self.board,
+            opponents: self.opponents,
+            n_folded: self.n_folded,
+            streets: self.streets,
+            pots: &pots,
+        };
+        let (state, method, stop_reason) = if let Some(holes) = self.showdown(&players) {
+            (
+                GameState::GameOver(outcomes(self.variant, &holes, &self.board).collect()),
+                Method::Exhaustive,
+                None,
+            )
+        } else if let Some(odds) = self.preflop_table.as_ref().and_then(|table| {
+            table.lookup(Spot {
+                players: &self.ranges(),
+                ..spot
+            })
+        }) {
+            (GameState::Undecided(odds), Method::Lookup, None)
+        } else if self.is_small(&players, cards_remaining) {
+            let odds =
+                exhaustive_odds_observed(spot, deck, observer).ok_or(GameError::Cancelled)?;
+            (GameState::Undecided(odds), Method::Exhaustive, None)
         } else {
-            GameOutcome {
-                state: GameState::Undecided(odds(
-                    self.opponents,
-                    self.n_folded,
-                    &self.players,
-                    &self.board,
-                    deck,
-                    permutations,
-                    rng,
-                )),
-                cards_remaining,
+            let (odds, reason) = odds_until(spot, deck, rule, &rng, observer);
+            (GameState::Undecided(odds), Method::MonteCarlo, Some(reason))
+        };
+        let samples = match &state {
+            GameState::GameOver(_) => 0,
+            GameState::Undecided(odds) => odds.scenarios(),
+        };
+        // with no scenarios evaluated, there is no equity to split the pots by
+        let decided = matches!(state, GameState::GameOver(_));
+        let pots = (!pots.is_empty() && (decided || samples > 0))
+            .then(|| self.pots(pots, &players, &state));
+        Ok(GameOutcome {
+            state,
+            cards_remaining,
+            method,
+            live_combos,
+            samples,
+            stop_reason,
+            pots,
+        })
+    }
+
+    fn side_pots(&self) -> Result<Vec<SidePot>, GameError> {
+        if self.contributions.is_empty() {
+            return Ok(vec![]);
+        }
+        let expected = self.players.len() + self.opponent_ranges.len() + self.opponents;
+        if self.contributions.len() != expected {
+            return Err(GameError::WrongNumberOfContributions(expected));
+        }
+        if let Some(amount) = self
+            .contributions
+            .iter()
+            .find(|amount| !(amount.is_finite() && **amount >= 0.0))
+        {
+            return Err(GameError::InvalidContribution(*amount));
+        }
+        Ok(side_pots(&self.contributions))
+    }
+
+    fn pots(&self, pots: Vec<SidePot>, players: &[Range], state: &GameState) -> Pots {
+        let equity = match state {
+            GameState::GameOver(_) => {
+                let holes = self.showdown(players).unwrap();
+                let masks = holes.iter().map(|hole| mask(hole)).collect::<Vec<_>>();
+                let shares = side_pot_shares(self.variant, &masks, mask(&self.board), &pots);
+                (0..masks.len())
+                    .map(|i| shares.iter().map(|pot| 100.0 * pot[i]).collect())
+                    .collect()
             }
+            GameState::Undecided(odds) => odds
+                .iter()
+                .map(|odds| {
+                    let equity = odds.pot_equity(pots.len());
+                    equity.map(|equity| 100.0 * equity).collect()
+                })
+                .collect(),
+        };
+        let chip_ev = zip(&equity, &self.contributions)
+            .map(|(equity, contribution)| {
+                let won = zip(equity, &pots).map(|(equity, pot)| equity / 100.0 * pot.amount);
+                won.sum::<f64>() - contribution
+            })
+            .collect();
+        Pots {
+            pots,
+            equity,
+            chip_ev,
+        }
+    }
+
+    fn deck(&self) -> Result<Deck, GameError> {
+        let ranges = self.ranges();
+        let hole_cards = self.variant.hole_cards();
+        // in stud, only the cards dealt so far need to be known
+        let valid = |combo: &HoleCards| match self.variant.is_stud() {
+            true => (1..=hole_cards).contains(&combo.len()),
+            false => combo.len() == hole_cards,
+        };
+        if let Some(i) = ranges
+            .iter()
+            .position(|range| !range.combos().iter().all(valid))
+        {
+            return Err(GameError::WrongNumberOfHoleCards {
+                player: i + 1,
+                expected: hole_cards,
+            });
+        }
+        if self.board.len() > self.variant.board_length() {
+            return Err(GameError::BoardTooLong(self.variant.board_length()));
+        }
+        let n_players = ranges.len() + self.opponents + self.n_folded;
+        if n_players * hole_cards + self.variant.board_length() > self.variant.deck().len() {
+            return Err(GameError::TooManyPlayers);
+        }
+        let mut deck = self.variant.deck();
+        // combos in a range using cards the deck lacks are dropped along with the dead ones
+        let fixed = ranges.iter().filter_map(Range::fixed).flatten();
+        let known = fixed.chain(&self.board).collect::<Vec<_>>();
+        if let Some(card) = known.iter().find(|card| !deck.contains(card)) {
+            return Err(GameError::NotInDeck(**card));
+        }
+        for card in known {
+            deck.remove(card)?;
+        }
+        Ok(deck)
+    }
+
+    fn ranges(&self) -> Vec<Range> {
+        [&self.players[..], &self.opponent_ranges].concat()
+    }
+
+    fn live_players(&self, deck: &Deck) -> Vec<Range> {
+        self.ranges()
+            .into_iter()
+            .map(|range| match range.fixed() {
+                Some(_) => range,
+                None => range.live(deck),
+            })
+            .collect()
+    }
+
+    pub fn nuts(&self, n: usize) -> Result<Nuts, GameError> {
+        self.nuts_cancellable(n, || false)
+    }
+
+    pub fn nuts_cancellable(
+        &self,
+        n: usize,
+        cancelled: impl Fn() -> bool + Sync,
+    ) -> Result<Nuts, GameError> {
+        if self.variant.is_stud() || self.board.len() < 3 {
+            return Err(GameError::NoFlop);
+        }
+        self.deck()?;
+        let deck = self.variant.deck().difference(self.board.iter().collect());
+        let holdings = Holdings::new(self.variant, &self.board, deck, cancelled)
+            .ok_or(GameError::Cancelled)?;
+        Ok(Nuts {
+            top: holdings.top(n),
+            strength: self
+                .players
+                .iter()
+                .map(|range| {
+                    range
+                        .fixed()
+                        .map(|hole_cards| holdings.strength(hole_cards))
+                })
+                .collect(),
         })
     }
 
-    fn is_over(&self) -> bool {
-        self.board.len() == BOARD_LENGTH && self.opponents == 0
+    pub fn texture(&self) -> Option<Texture> {
+        texture(self.variant, &self.board)
+    }
+
+    pub fn draws(&self) -> Result<Option<Vec<DrawDistribution>>, GameError> {
+        let deck = self.deck()?;
+        let distribution = |range: &Range| {
+            range.weighted_combos().try_fold(
+                DrawDistribution::default(),
+                |distribution, (combo, weight)| {
+                    Some(distribution.update(&draws(self.variant, combo, &self.board)?, weight))
+                },
+            )
+        };
+        let players = self.live_players(&deck);
+        Ok(players[..self.players.len()]
+            .iter()
+            .map(distribution)
+            .collect())
+    }
+
+    /// The cards that would make the hero the winner if dealt next.
+    pub fn outs(&self) -> Result<Vec<Out>, GameError> {
+        if self.variant.streets().is_empty() || !(3..5).contains(&self.board.len()) {
+            return Err(GameError::NoNextCard);
+        }
+        if self.variant.is_hi_lo() {
+            return Err(GameError::HiLo);
+        }
+        let deck = self.deck()?;
+        let players = self
+            .players
+            .iter()
+            .map(|range| range.fixed().filter(|_| !self.has_opponents()).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(GameError::UnknownHoleCards)?;
+        Ok(outs(self.variant, &players, &self.board, deck))
+    }
+
+    pub fn next_cards(
+        &self,
+        rng: impl SplitRng + Sync,
+        rule: StoppingRule,
+    ) -> Result<NextCards, GameError> {
+        self.next_cards_observed(rng, rule, |_, _, _| ControlFlow::Continue(()))
+    }
+
+    /// Like `next_cards`, but `observer` watches each game played and can break.
+    pub fn next_cards_observed(
+        &self,
+        rng: impl SplitRng + Sync,
+        rule: StoppingRule,
+        mut observer: impl FnMut(&Odds, usize, usize) -> ControlFlow<()>,
+    ) -> Result<NextCards, GameError> {
+        if self.variant.streets().is_empty() || !(3..5).contains(&self.board.len()) {
+            return Err(GameError::NoNextCard);
+        }
+        let n_players = self.players.len();
+        let mut play = |game: &Game, stream| {
+            let outcome = game.play_observed(rng.stream(stream), rule, &mut observer)?;
+            match outcome.stop_reason {
+                Some(StopReason::Cancelled) => Err(GameError::Cancelled),
+                _ => Ok(outcome.state.equity_percent(n_players)),
+            }
+        };
+        let equity = play(self, 0)?;
+        let mut cards = vec![];
+        for (i, card) in self.deck()?.consume().enumerate() {
+            let game = Game {
+                players: self.players.clone(),
+                opponent_ranges: self.opponent_ranges.clone(),
+                board: [&self.board[..], &[card]].concat(),
+                preflop_table: self.preflop_table.clone(),
+                contributions: self.contributions.clone(),
+                ..*self
+            };
+            let next = match play(&game, i as u64 + 1) {
+                Ok(equity) => equity,
+                // the card could be in every combo of some player's range
+                Err(GameError::EmptyRange(_) | GameError::ConflictingRanges) => continue,
+                Err(error) => return Err(error),
+            };
+            let effect = match next[0] - equity[0] {
+                change if change >= NEUTRAL_MARGIN => Effect::ImprovesHero,
+                change if change <= -NEUTRAL_MARGIN => Effect::ImprovesVillain,
+                _ => Effect::Neutral,
+            };
+            cards.push(NextCard {
+                card,
+                equity: next,
+                effect,
+            });
+        }
+        Ok(NextCards { equity, cards })
+    }
+
+    fn has_opponents(&self) -> bool {
+        self.opponents > 0 || !self.opponent_ranges.is_empty()
+    }
+
+    fn is_small(&self, players: &[Range], cards_remaining: usize) -> bool {
+        let hole_cards = self.variant.hole_cards();
+        let incomplete =
+            |range: &Range| range.combos().iter().any(|combo| combo.len() < hole_cards);
+        if players
+            .iter()
+            .any(|range| range.is_weighted() || incomplete(range))
+        {
+            return false;
+        }
+        let unknown_board_cards = self.variant.board_length() - self.board.len();
+        let variable = players
+            .iter()
+            .filter(|range| range.fixed().is_none())
+            .collect::<Vec<_>>();
+        let cards_remaining = cards_remaining - hole_cards * variable.len();
+        let combos = variable
+            .iter()
+            .try_fold(1u64, |total, range| total.checked_mul(range.len() as u64));
+        scenarios(
+            cards_remaining,
+            unknown_board_cards,
+            self.opponents,
+            hole_cards,
+        )
+        .zip(combos)
+        .and_then(|(scenarios, combos)| scenarios.checked_mul(combos))
+        .map_or(false, |n| n <= EXHAUSTIVE_THRESHOLD)
+    }
+
+    fn showdown(&self, players: &[Range]) -> Option<Vec<HoleCards>> {
+        if self.board.len() != self.variant.board_length() || self.has_opponents() {
+            return None;
+        }
+        players
+            .iter()
+            .map(|range| {
+                let hole = range.fixed()?;
+                (hole.len() == self.variant.hole_cards()).then(|| hole.clone())
+            })
+            .collect()
+    }
+}
+
+fn is_feasible(ranges: &[Range], dealt: &mut Vec<Card>) -> bool {
+    let Some((range, rest)) = ranges.split_first() else {
+        return true;
+    };
+    range.combos().iter().any(|combo| {
+        if combo.iter().any(|card| dealt.contains(card)) {
+            return false;
+        }
+        dealt.extend(combo);
+        let feasible = is_feasible(rest, dealt);
+        dealt.truncate(dealt.len() - combo.len());
+        feasible
+    })
+}
+
+#[derive(Debug)]
+pub enum GameError {
+    Deck(DeckError),
+    EmptyRange(usize),
+    ConflictingRanges,
+    WrongNumberOfHoleCards { player: usize, expected: usize },
+    BoardTooLong(usize),
+    TooManyPlayers,
+    NotInDeck(Card),
+    NoNextCard,
+    UnknownHoleCards,
+    HiLo,
+    NoFlop,
+    WrongNumberOfContributions(usize),
+    InvalidContribution(f64),
+    Cancelled,
+}
+
+impl From<DeckError> for GameError {
+    fn from(error: DeckError) -> Self {
+        GameError::Deck(error)
+    }
+}
+
+impl Display for GameError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            GameError::Deck(error) => write!(fmt, "{}", error),
+            GameError::EmptyRange(player) => {
+                write!(fmt, "no possible hole cards for player {}", player)
+            }
+            GameError::ConflictingRanges => write!(fmt, "players' ranges conflict"),
+            GameError::WrongNumberOfHoleCards { player, expected } => {
+                write!(fmt, "player {} must hold {} hole cards", player, expected)
+            }
+            GameError::BoardTooLong(max) => {
+                write!(fmt, "the board can have at most {} cards", max)
+            }
+            GameError::TooManyPlayers => write!(fmt, "not enough cards for all players"),
+            GameError::NotInDeck(card) => write!(fmt, "{} is not in the deck", card),
+            GameError::NoNextCard => {
+                write!(
+                    fmt,
+                    "the next card can only be dealt on the flop or the turn"
+                )
+            }
+            GameError::UnknownHoleCards => write!(fmt, "every player's hole cards must be known"),
+            GameError::HiLo => write!(fmt, "outs can't be counted in hi/lo games"),
+            GameError::NoFlop => write!(fmt, "the flop must be dealt first"),
+            GameError::WrongNumberOfContributions(expected) => write!(
+                fmt,
+                "expected a contribution for each of the {} players still in",
+                expected
+            ),
+            GameError::InvalidContribution(amount) => {
+                write!(fmt, "invalid contribution: {}", amount)
+            }
+            GameError::Cancelled => write!(fmt, "cancelled"),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{mask, parse_cards};
+
+    fn game(players: &[&str], board: &str) -> Game {
+        let players = players
+            .iter()
+            .map(|cards| Range::from(parse_cards(cards)))
+            .collect();
+        Game::new(players, parse_cards(board), 0, 0)
+    }
+
+    #[test]
+    fn test_next_cards() {
+        let rule = StoppingRule::samples(0);
+        let game = game(&["Ad Ks", "8h 8s"], "8c Ah As 9c");
+        let rng = fastrand::Rng::with_seed(1);
+        let NextCards { equity, cards } = game.next_cards(rng, rule).unwrap();
+        assert!((equity[0] - 100.0 * 7.0 / 44.0).abs() < 1e-9);
+        assert_eq!(44, cards.len());
+        // the last ace, three kings and three nines on the river win it for player 1
+        let improving = cards
+            .iter()
+            .filter(|next| next.effect == Effect::ImprovesHero)
+            .map(|next| next.card)
+            .collect::<Vec<_>>();
+        let expected = parse_cards("Ac Kc Kd Kh 9d 9h 9s");
+        assert_eq!(mask(&expected), mask(&improving));
+        assert_eq!(expected.len(), improving.len());
+        assert!(cards
+            .iter()
+            .all(|next| next.equity[0] == 100.0 || next.effect == Effect::ImprovesVillain));
+
+        let game = self::game(&["Ad Ks", "8h 8s"], "8c Ah As 9c Kh");
+        let rng = fastrand::Rng::with_seed(1);
+        assert!(matches!(
+            game.next_cards(rng, rule),
+            Err(GameError::NoNextCard)
+        ));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let game = game(&["Ad Ks", "8h 8s"], "8c Ah As");
+        let rule = StoppingRule::samples(1000);
+        let rng = fastrand::Rng::with_seed(1);
+        let mut snapshots = vec![];
+        let outcome = game.play_observed(rng.clone(), rule, |odds, scenarios, total| {
+            snapshots.push((odds.scenarios(), scenarios, total));
+            ControlFlow::Break(())
+        });
+        // the odds are exact, so there is nothing to show for an unfinished enumeration
+        assert!(matches!(outcome, Err(GameError::Cancelled)));
+        assert_eq!(vec![(990, 990, 990)], snapshots);
+        let cancel = |_: &Odds, _, _| ControlFlow::Break(());
+        assert!(matches!(
+            game.next_cards_observed(rng, rule, cancel),
+            Err(GameError::Cancelled)
+        ));
+        assert!(matches!(
+            game.nuts_cancellable(1, || true),
+            Err(GameError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_outs_need_known_hole_cards() {
+        let game = game(&["Ah Kh", "Qs Qc"], "2h 7h 9c");
+        assert_eq!(15, game.outs().unwrap().len());
+        let game = Game::new(
+            vec![Range::from(parse_cards("Ah Kh")), "QQ".parse().unwrap()],
+            parse_cards("2h 7h 9c"),
+            0,
+            0,
+        );
+        assert!(matches!(game.outs(), Err(GameError::UnknownHoleCards)));
+        let hi_lo = Game::new(
+            ["Ah Kh 2c 3d", "Qs Qc Jd Td"]
+                .map(|cards| Range::from(parse_cards(cards)))
+                .to_vec(),
+            parse_cards("2h 7h 9c"),
+            0,
+            0,
+        )
+        .with_variant(Variant::OmahaHiLo);
+        assert!(matches!(hi_lo.outs(), Err(GameError::HiLo)));
+    }
+
+    #[test]
+    fn test_nuts() {
+        let game = Game::new(
+            vec![Range::from(parse_cards("Ah Kh")), "QQ".parse().unwrap()],
+            parse_cards("2h 7h 9h"),
+            0,
+            0,
+        );
+        let Nuts { top, strength } = game.nuts(1).unwrap();
+        assert_eq!(parse_cards("Ah Kh"), top[0].hole_cards);
+        assert_eq!(vec![Some(100.0), None], strength);
+        let preflop = Game::new(vec![Range::from(parse_cards("Ah Kh"))], vec![], 0, 0);
+        assert!(matches!(preflop.nuts(1), Err(GameError::NoFlop)));
+    }
+
+    #[test]
+    fn test_opponent_ranges() {
+        let rule = StoppingRule::samples(1000);
+        let equity = |game: &Game, n_players| {
+            let rng = fastrand::Rng::with_seed(1);
+            game.play(rng, rule)
+                .unwrap()
+                .state
+                .equity_percent(n_players)
+        };
+        let ranges = || vec!["KK".parse().unwrap(), "AA".parse().unwrap()];
+        let known = Game::new(
+            [vec![Range::from(parse_cards("Ah As"))], ranges()].concat(),
+            parse_cards("2c 7d 9h"),
+            0,
+            0,
+        );
+        let opponents = game(&["Ah As"], "2c 7d 9h").with_opponent_ranges(ranges());
+        // the opponents are dealt from their ranges just as known players are, but are reported
+        // together
+        let known = equity(&known, 3);
+        let merged = equity(&opponents, 1);
+        assert_eq!(2, merged.len());
+        assert!((known[0] - merged[0]).abs() < 1e-9);
+        assert!(((known[1] + known[2]) / 2.0 - merged[1]).abs() < 1e-9);
+        assert_ne!(game(&["Ah As"], "2c 7d 9h").key(), opponents.key());
+
+        let mut contributions = opponents.with_contributions(vec![10.0, 10.0]);
+        let rng = fastrand::Rng::with_seed(1);
+        assert!(matches!(
+            contributions.play(rng.clone(), rule),
+            Err(GameError::WrongNumberOfContributions(3))
+        ));
+        contributions.contributions.push(10.0);
+        assert_eq!(
+            3,
+            contributions
+                .play(rng, rule)
+                .unwrap()
+                .pots
+                .unwrap()
+                .chip_ev
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_short_deck_ranges() {
+        let players = vec![Range::from(parse_cards("Ah Kh")), "22+".parse().unwrap()];
+        let game = Game::new(players, vec![], 0, 0).with_variant(Variant::ShortDeck);
+        let rng = fastrand::Rng::with_seed(1);
+        let outcome = game.play(rng.clone(), StoppingRule::samples(100)).unwrap();
+        // only the sixes to aces are in the deck, less the ace and king already dealt
+        assert_eq!(vec![1.0, 7.0 * 6.0 + 2.0 * 3.0], outcome.live_combos);
+        let fixed = Game::new(vec![Range::from(parse_cards("Ah 2h"))], vec![], 0, 0)
+            .with_variant(Variant::ShortDeck);
+        assert!(matches!(
+            fixed.play(rng, StoppingRule::samples(100)),
+            Err(GameError::NotInDeck(_))
+        ));
+    }
+
+    #[test]
+    fn test_side_pots() {
+        let players = ["Ac Kc", "Kh Kd", "Qc Jc"];
+        let contributions = vec![100.0, 50.0, 150.0];
+        let river = game(&players, "2c 7d 9h Ks 4s").with_contributions(contributions.clone());
+        let rng = fastrand::Rng::with_seed(1);
+        let pots = river
+            .play(rng.clone(), StoppingRule::samples(1000))
+            .unwrap()
+            .pots
+            .unwrap();
+        // the kings win the main pot, the ace the side pot, and the last 50 are returned
+        assert_eq!(vec![0.0, 100.0, -100.0], pots.chip_ev);
+        assert_eq!(vec![0.0, 100.0, 0.0], pots.equity[0]);
+
+        let turn = game(&players, "2c 7d 9h Ks").with_contributions(contributions);
+        let outcome = turn.play(rng.clone(), StoppingRule::samples(1000)).unwrap();
+        let (GameState::Undecided(odds), Some(pots)) = (outcome.state, outcome.pots) else {
+            panic!("the game isn't over on the turn");
+        };
+        let equity = odds
+            .iter()
+            .map(|odds| odds.equity_percent())
+            .collect::<Vec<_>>();
+        assert!((equity[1] - pots.equity[1][0]).abs() < 1e-9);
+        assert_eq!(0.0, pots.equity[1][1]);
+        assert!(pots.chip_ev.iter().sum::<f64>().abs() < 1e-9);
+
+        // every pot is reported with finite equity, unless there were no samples to split it by
+        let preflop = Game::new(turn.players, vec![], 1, 0)
+            .with_contributions(vec![100.0, 50.0, 150.0, 150.0]);
+        let pots = preflop
+            .play(rng.clone(), StoppingRule::samples(1000))
+            .unwrap()
+            .pots
+            .unwrap();
+        assert!(pots
+            .equity
+            .iter()
+            .flatten()
+            .all(|equity| equity.is_finite()));
+        assert!(pots.equity.iter().all(|equity| equity.len() == 3));
+        assert!(pots.chip_ev.iter().all(|ev| ev.is_finite()));
+        let outcome = preflop.play(rng, StoppingRule::samples(0)).unwrap();
+        assert_eq!(0, outcome.samples);
+        assert!(outcome.pots.is_none());
+
+        let missing = game(&players, "2c 7d 9h").with_contributions(vec![10.0]);
+        assert!(matches!(
+            missing.play(fastrand::Rng::with_seed(1), StoppingRule::samples(1)),
+            Err(GameError::WrongNumberOfContributions(3))
+        ));
+    }
+
+    #[test]
+    fn test_canonical() {
+        let (a, _) = game(&["As Ks", "Qh Qd"], "2c 7d 9h").canonical();
+        let (b, permutation) = game(&["Ah Kh", "Qc Qs"], "2d 7s 9c").canonical();
+        assert_eq!(a.key(), b.key());
+        assert_ne!(a.key(), game(&["As Ks", "Qh Qs"], "2c 7d 9h").key());
+        let restored = b
+            .board
+            .iter()
+            .map(|card| permutation.inverse().apply(*card));
+        assert_eq!(parse_cards("2d 7s 9c"), restored.collect::<Vec<_>>());
     }
 }
@@ -1,42 +1,54 @@
 use crate::card::Rank::*;
-use crate::card::{Card, Cards, Players};
-use crate::HOLE_CARDS_PER_PLAYER;
+use crate::card::{Card, Cards, Rank};
 use itertools::Itertools;
 use std::cmp::{Ordering, Reverse};
 use std::iter::zip;
 use HandType::*;
 
-pub fn hands(players: &Players, board: &Cards) -> Vec<Hand> {
-    players
-        .iter()
-        .map(|hole| hand([hole, board].concat()))
-        .collect_vec()
+pub fn hand(cards: Vec<Card>) -> Hand {
+    best_hand(cards, Deuce, Hand::cmp)
+}
+
+/// Like `hand`, but under short-deck rules.
+pub fn short_deck_hand(cards: Vec<Card>) -> Hand {
+    best_hand(cards, Six, short_deck_cmp)
 }
 
-pub fn hand(mut cards: Vec<Card>) -> Hand {
+pub fn short_deck_cmp(hand1: &Hand, hand2: &Hand) -> Ordering {
+    let strength = |hand: &Hand| match hand.hand_type {
+        Flush => FullHouse as u8,
+        FullHouse => Flush as u8,
+        hand_type => hand_type as u8,
+    };
+    strength(hand1)
+        .cmp(&strength(hand2))
+        .then_with(|| hand1.cmp(hand2))
+}
+
+fn best_hand(mut cards: Vec<Card>, lowest: Rank, cmp: fn(&Hand, &Hand) -> Ordering) -> Hand {
     cards.sort_unstable_by_key(|x| Reverse(x.rank));
     assert!(
         cards.iter().dedup_with_count().any(|(dupes, _)| dupes > 0),
         "bug: all cards must be unique"
     );
     assert!(
-        cards.len() <= Hand::HAND_SIZE + HOLE_CARDS_PER_PLAYER,
+        cards.len() <= Hand::MAX_CARDS,
         "bug: too many cards in hand"
     );
     let flush = find_flush(&cards);
     [
-        find_straight_flush(&flush, &cards),
+        find_straight_flush(&flush, &cards, lowest),
         flush,
         find_groups(&cards),
-        find_straight(&cards),
+        find_straight(&cards, lowest),
     ]
     .into_iter()
     .flatten()
-    .max()
+    .max_by(cmp)
     .unwrap_or_else(|| find_high_card(&cards))
 }
 
-fn find_straight_flush(flush: &Option<Hand>, all_cards: &Cards) -> Option<Hand> {
+fn find_straight_flush(flush: &Option<Hand>, all_cards: &Cards, lowest: Rank) -> Option<Hand> {
     match flush {
         Some(Hand {
             hand_type: Flush,
@@ -47,7 +59,7 @@ fn find_straight_flush(flush: &Option<Hand>, all_cards: &Cards) -> Option<Hand>
                 .filter(|card| card.suit == *suit)
                 .copied()
                 .collect_vec();
-            let straight = find_straight(&cards)?;
+            let straight = find_straight(&cards, lowest)?;
             Some(Hand {
                 hand_type: StraightFlush,
                 ..straight
@@ -89,7 +101,9 @@ fn find_groups(cards: &Cards) -> Option<Hand> {
     }
 }
 
-fn groups(cards: &Cards) -> (Vec<[Card; 4]>, Vec<[Card; 3]>, Vec<[Card; 2]>) {
+type Groups = (Vec<[Card; 4]>, Vec<[Card; 3]>, Vec<[Card; 2]>);
+
+fn groups(cards: &Cards) -> Groups {
     let grouped_cards = cards.iter().group_by(|c| c.rank);
     let (mut four, mut three, mut pairs) = (vec![], vec![], vec![]);
     for (_rank, cards) in grouped_cards.into_iter() {
@@ -134,7 +148,7 @@ fn find_flush(cards: &Cards) -> Option<Hand> {
     })
 }
 
-fn find_straight(cards: &Cards) -> Option<Hand> {
+fn find_straight(cards: &Cards, lowest: Rank) -> Option<Hand> {
     let mut straight = cards
         .iter()
         .dedup_by(|card1, card2| card1.rank == card2.rank)
@@ -143,10 +157,10 @@ fn find_straight(cards: &Cards) -> Option<Hand> {
         .into_iter()
         .map(|(_, group)| group.map(|(_i, card)| *card).collect_vec())
         .find(|v| v.len() >= 4)?;
-    if let (ace @ Card { rank: Ace, .. }, Card { rank: Deuce, .. }) =
-        (cards.first()?, straight.last()?)
-    {
-        straight.push(*ace);
+    if let (ace @ Card { rank: Ace, .. }, Card { rank, .. }) = (cards.first()?, straight.last()?) {
+        if *rank == lowest {
+            straight.push(*ace);
+        }
     }
     straight.truncate(Hand::HAND_SIZE);
     Some(Hand {
@@ -157,7 +171,7 @@ fn find_straight(cards: &Cards) -> Option<Hand> {
 
 #[cfg(test)]
 pub fn gen_flushes() -> impl Iterator<Item = Vec<Card>> {
-    use crate::card::{Rank, Suit};
+    use crate::card::Suit;
     use std::iter::repeat;
     shuffled(&Rank::ALL)
         .combinations(5)
@@ -180,7 +194,7 @@ pub fn gen_flushes() -> impl Iterator<Item = Vec<Card>> {
 
 #[cfg(test)]
 pub fn gen_straights() -> impl Iterator<Item = Vec<Card>> {
-    use crate::card::{Rank, Suit};
+    use crate::card::Suit;
     Suit::ALL
         .into_iter()
         .combinations_with_replacement(5)
@@ -204,7 +218,7 @@ pub fn gen_straights() -> impl Iterator<Item = Vec<Card>> {
 
 #[cfg(test)]
 pub fn gen_straight_flushes() -> impl Iterator<Item = Vec<Card>> {
-    use crate::card::{Rank, Suit};
+    use crate::card::Suit;
     shuffled(&Suit::ALL).flat_map(move |suit| {
         Rank::ALL_WITH_BOTH_ACES
             .windows(5)
@@ -239,7 +253,8 @@ pub struct Hand {
 }
 
 impl Hand {
-    const HAND_SIZE: usize = 5;
+    pub(crate) const HAND_SIZE: usize = 5;
+    const MAX_CARDS: usize = 7;
 }
 
 impl PartialEq for Hand {
@@ -280,24 +295,28 @@ pub enum HandType {
     StraightFlush,
 }
 
+impl HandType {
+    pub const ALL: [HandType; 9] = [
+        HighCard,
+        Pair,
+        TwoPair,
+        ThreeOfAKind,
+        Straight,
+        Flush,
+        FullHouse,
+        FourOfAKind,
+        StraightFlush,
+    ];
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{
-        calc::HandTypeDistribution,
-        card::{Rank, Suit},
-        Deck,
-    };
+    use crate::card::{mask, parse_cards};
+    use crate::eval::evaluate_short_deck;
+    use crate::{calc::HandTypeDistribution, card::Suit, Deck};
     use std::iter::repeat;
 
-    fn parse_card(raw: &str) -> Card {
-        raw.parse().unwrap()
-    }
-
-    fn parse_cards(raw: &str) -> Vec<Card> {
-        raw.split(' ').map(parse_card).collect()
-    }
-
     #[test]
     fn test_handles_all_valid_hands() {
         use rayon::prelude::*;
@@ -329,7 +348,7 @@ mod tests {
         let cards = parse_cards("2c 3c 4c 5c Kh");
         let result = hand(cards);
         assert_eq!(result.hand_type, HighCard);
-        assert_eq!(result.cards[0], parse_card("Kh"));
+        assert_eq!(result.cards[0], parse_cards("Kh")[0]);
     }
 
     #[test]
@@ -459,6 +478,38 @@ mod tests {
         assert_eq!(result.cards.to_vec(), parse_cards("6c 5h 4s 3d 2s"));
     }
 
+    #[test]
+    fn test_short_deck_straight() {
+        let cards = parse_cards("As 6s 7d 8d 9h Kc Kd");
+        let result = short_deck_hand(cards.clone());
+        assert_eq!(result.hand_type, Straight);
+        assert_eq!(result.cards.to_vec(), parse_cards("9h 8d 7d 6s As"));
+        assert_eq!(hand(cards).hand_type, Pair);
+    }
+
+    #[test]
+    fn test_short_deck_straight_from_rank() {
+        for (cards, expected) in [
+            ("As 6s 7d 8d 9h Kc Kd", "9h 8d 7d 6s As"),
+            ("As 6s 7s 8s 9s Kc Kd", "9s 8s 7s 6s As"),
+        ] {
+            let cards = mask(&parse_cards(cards));
+            let hand = evaluate_short_deck(cards).hand(cards);
+            assert_eq!(parse_cards(expected), hand.cards.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_short_deck_flush_beats_full_house() {
+        let flush = hand(parse_cards("As Ks 9s 8s 6s"));
+        let full_house = hand(parse_cards("Ah Ad Ac Kd Kh"));
+        assert!(flush < full_house);
+        assert_eq!(Ordering::Greater, short_deck_cmp(&flush, &full_house));
+        let straight = short_deck_hand(parse_cards("Ts 6s 7d 8d 9h"));
+        let wheel = short_deck_hand(parse_cards("As 6s 7d 8d 9h"));
+        assert_eq!(Ordering::Greater, short_deck_cmp(&straight, &wheel));
+    }
+
     #[test]
     fn test_straight_flush_with_pair() {
         let cards = parse_cards("As 2d 2s 3s 4s 5s 5c");
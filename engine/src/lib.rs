@@ -3,18 +3,48 @@ mod calc;
 mod card;
 mod deck;
 mod display;
+mod draw;
+mod ev;
+mod eval;
 mod floyd;
 mod game;
 mod hand;
+mod nuts;
+mod outs;
 mod parse;
+mod pots;
+mod preflop;
+mod range;
+mod suits;
+mod texture;
+mod variant;
 
 #[cfg(test)]
 #[global_allocator]
 static ALLOCATOR: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-pub use calc::{odds, outcomes, HandOutcome, Odds, Outcome, Player, BOARD_LENGTH};
-pub use card::{Card, HOLE_CARDS_PER_PLAYER};
+pub use calc::{
+    confidence_interval, exhaustive_odds, exhaustive_odds_observed, odds, odds_until, outcomes,
+    scenarios, HandOutcome, Odds, Outcome, Player, Spot, StopReason, StoppingRule,
+    EXHAUSTIVE_THRESHOLD, Z_95,
+};
+pub use card::Card;
 pub use deck::Deck;
-pub use floyd::Rng;
-pub use game::{Game, GameOutcome, GameState};
-pub use hand::Hand;
+pub use draw::{draws, Draw, DrawDistribution};
+pub use ev::{Ev, Stakes, StakesError};
+pub use eval::{evaluate, HandRank};
+pub use floyd::{split_seed, Rng, SplitRng};
+pub use game::{
+    Effect, Game, GameError, GameKey, GameOutcome, GameState, Method, NextCard, NextCards, Nuts,
+    Pots, NEUTRAL_MARGIN,
+};
+pub use hand::{Hand, HandType};
+pub use nuts::{Holding, Holdings};
+pub use outs::{outs, Out};
+pub use parse::{RangeParseError, VariantParseError};
+pub use pots::{side_pots, SidePot};
+pub use preflop::{PreflopTable, PreflopTableError, StartingHand, MAX_OPPONENTS};
+pub use range::Range;
+pub use suits::SuitPermutation;
+pub use texture::{texture, Connectedness, HighCard, Pairing, Suits, Texture};
+pub use variant::Variant;
@@ -0,0 +1,126 @@
+use crate::card::{mask, Cards, HoleCards};
+use crate::deck::Deck;
+use crate::eval::HandRank;
+use crate::hand::Hand;
+use crate::variant::Variant;
+use itertools::Itertools;
+use rayon::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct Holding {
+    pub hole_cards: HoleCards,
+    pub hand: Hand,
+    /// 1 for the nuts; equally strong holdings share a rank.
+    pub rank: usize,
+}
+
+pub struct Holdings {
+    variant: Variant,
+    board: HoleCards,
+    ranked: Vec<(u64, HandRank)>,
+}
+
+impl Holdings {
+    /// Evaluates every holding in `deck`, unless `cancelled` returns true first.
+    pub fn new(
+        variant: Variant,
+        board: &Cards,
+        deck: Deck,
+        cancelled: impl Fn() -> bool + Sync,
+    ) -> Option<Self> {
+        let board_mask = mask(board);
+        let mut ranked = deck
+            .iter()
+            .combinations(variant.hole_cards())
+            .map(|hole_cards| mask(&hole_cards))
+            .collect_vec()
+            .into_par_iter()
+            .map(|hole_cards| {
+                let rank = (!cancelled()).then(|| variant.evaluate(hole_cards, board_mask))?;
+                Some((hole_cards, rank))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        ranked.sort_unstable_by(|(a_cards, a), (b_cards, b)| b.cmp(a).then(a_cards.cmp(b_cards)));
+        Some(Self {
+            variant,
+            board: board.to_vec(),
+            ranked,
+        })
+    }
+
+    pub fn top(&self, n: usize) -> Vec<Holding> {
+        let mut rank = 0;
+        let mut previous = None;
+        self.ranked
+            .iter()
+            .take(n)
+            .map(|(hole_cards, hand_rank)| {
+                if previous != Some(hand_rank) {
+                    rank += 1;
+                    previous = Some(hand_rank);
+                }
+                let hole_cards = Deck::from_mask(*hole_cards)
+                    .iter()
+                    .sorted_by(|a, b| b.rank.cmp(&a.rank))
+                    .collect_vec();
+                Holding {
+                    hand: self.variant.hand(&hole_cards, &self.board),
+                    hole_cards,
+                    rank,
+                }
+            })
+            .collect()
+    }
+
+    /// The percentage of holdings `hole_cards` beat, counting ties as half.
+    pub fn strength(&self, hole_cards: &Cards) -> f64 {
+        let own = mask(hole_cards);
+        let rank = self.variant.evaluate(own, mask(&self.board));
+        let (beaten, tied, total) = self
+            .ranked
+            .iter()
+            .filter(|(other, _)| other & own == 0)
+            .fold((0, 0, 0), |(beaten, tied, total), (_, other)| {
+                (
+                    beaten + usize::from(rank > *other),
+                    tied + usize::from(rank == *other),
+                    total + 1,
+                )
+            });
+        100.0 * (beaten as f64 + tied as f64 / 2.0) / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::hand::HandType;
+
+    fn holdings(board: &str) -> Holdings {
+        let board = parse_cards(board);
+        let deck = Deck::default().difference(board.iter().collect());
+        Holdings::new(Variant::Holdem, &board, deck, || false).unwrap()
+    }
+
+    #[test]
+    fn test_nuts() {
+        let holdings = holdings("Ah Kh Qh 2c 7d");
+        let top = holdings.top(3);
+        assert_eq!(parse_cards("Jh Th"), top[0].hole_cards);
+        assert_eq!(HandType::StraightFlush, top[0].hand.hand_type);
+        assert_eq!(1, top[0].rank);
+        // the ace-high flush with the jack of hearts
+        assert_eq!(HandType::Flush, top[1].hand.hand_type);
+        assert_eq!(2, top[1].rank);
+    }
+
+    #[test]
+    fn test_strength() {
+        let holdings = holdings("2c 7d 9h Ks 4s");
+        assert_eq!(100.0, holdings.strength(&parse_cards("Kh Kd")));
+        assert!(holdings.strength(&parse_cards("3c 5d")) < 1.0);
+        let top_pair = holdings.strength(&parse_cards("Kc Qc"));
+        assert!((80.0..95.0).contains(&top_pair), "{}", top_pair);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::calc::{outcomes, Outcome};
+use crate::card::{Card, Cards, Players};
+use crate::deck::Deck;
+use crate::hand::HandType;
+use crate::variant::Variant;
+use itertools::Itertools;
+
+/// A card that would make the hero the winner if dealt next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Out {
+    pub card: Card,
+    pub hand_type: HandType,
+    /// Whether it improves no opponent's hand type.
+    pub clean: bool,
+}
+
+/// None if the hero is already winning.
+pub fn outs(variant: Variant, players: &Players, board: &Cards, deck: Deck) -> Vec<Out> {
+    let before = outcomes(variant, players, board).collect_vec();
+    if before[0].outcome == Outcome::Win {
+        return vec![];
+    }
+    deck.consume()
+        .filter_map(|card| {
+            let board = [board, &[card]].concat();
+            let after = outcomes(variant, players, &board).collect_vec();
+            (after[0].outcome == Outcome::Win).then(|| Out {
+                card,
+                hand_type: after[0].hand.hand_type,
+                clean: before
+                    .iter()
+                    .zip(&after)
+                    .skip(1)
+                    .all(|(before, after)| before.hand.hand_type == after.hand.hand_type),
+            })
+        })
+        .sorted_by(|a, b| b.hand_type.partial_cmp(&a.hand_type).unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::hand::HandType::*;
+
+    #[test]
+    fn test_flush_draw_outs() {
+        let players = vec![parse_cards("Ah Kh"), parse_cards("Qs Qc")];
+        let board = parse_cards("2h 7h 9c Td");
+        let deck =
+            Deck::default().difference(players.iter().flatten().chain(&board).copied().collect());
+        let outs = outs(Variant::Holdem, &players, &board, deck);
+        // nine hearts, three aces and three kings
+        assert_eq!(15, outs.len());
+        assert_eq!(9, outs.iter().filter(|out| out.hand_type == Flush).count());
+        assert!(outs[..9].iter().all(|out| out.hand_type == Flush));
+        assert!(outs[9..].iter().all(|out| out.hand_type == Pair));
+        // the nine and ten of hearts also give the queens two pair, and the queen a set
+        let dirty = outs.iter().filter(|out| !out.clean).map(|out| out.card);
+        assert_eq!(parse_cards("9h Th Qh"), dirty.collect_vec());
+    }
+
+    #[test]
+    fn test_no_outs_when_ahead() {
+        let players = vec![parse_cards("As Ad"), parse_cards("Kh Qh")];
+        let board = parse_cards("2h 7h 9c");
+        let deck =
+            Deck::default().difference(players.iter().flatten().chain(&board).copied().collect());
+        assert_eq!(
+            Vec::<Out>::new(),
+            outs(Variant::Holdem, &players, &board, deck)
+        );
+        // from behind, the same cards are outs for the other player
+        let swapped = vec![players[1].clone(), players[0].clone()];
+        assert!(!outs(Variant::Holdem, &swapped, &board, deck).is_empty());
+    }
+}
@@ -1,4 +1,7 @@
 use crate::card::{Card, Rank, Suit};
+use crate::range::Range;
+use crate::variant::Variant;
+use itertools::Itertools;
 use std::error::Error;
 use std::fmt::Display;
 use std::str::FromStr;
@@ -80,3 +83,241 @@ impl Display for CardParseError {
         }
     }
 }
+
+impl FromStr for Range {
+    type Err = RangeParseError;
+
+    fn from_str(string: &str) -> Result<Range, RangeParseError> {
+        let range = string
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(parse_range_item)
+            .fold_ok(Range::new([]), Range::union)?;
+        if range.is_empty() {
+            Err(RangeParseError::EmptyRange)
+        } else {
+            Ok(range)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Suitedness {
+    Pair,
+    Suited,
+    Offsuit,
+    Any,
+}
+
+fn parse_range_item(item: &str) -> Result<Range, RangeParseError> {
+    if let Some((hands, weight)) = item.split_once(':') {
+        let weight = weight
+            .trim()
+            .parse()
+            .ok()
+            .filter(|weight| (0.0..=1.0).contains(weight))
+            .ok_or_else(|| RangeParseError::InvalidWeight(weight.into()))?;
+        return Ok(parse_range_item(hands.trim())?.weighted(weight));
+    }
+    if !item.is_ascii() {
+        return Err(RangeParseError::InvalidHand(item.into()));
+    }
+    if let Some((from, to)) = item.split_once('-') {
+        let (high1, low1, suitedness1) = parse_hand_class(from)?;
+        let (high2, low2, suitedness2) = parse_hand_class(to)?;
+        let span = || RangeParseError::InvalidSpan(item.into());
+        if suitedness1 != suitedness2 {
+            return Err(span());
+        }
+        if suitedness1 == Suitedness::Pair {
+            return Ok(pairs_between(high1.min(high2), high1.max(high2)));
+        }
+        if high1 != high2 {
+            return Err(span());
+        }
+        Ok(kickers_between(
+            high1,
+            low1.min(low2),
+            low1.max(low2),
+            suitedness1,
+        ))
+    } else if let Some(class) = item.strip_suffix('+') {
+        let (high, low, suitedness) = parse_hand_class(class)?;
+        if suitedness == Suitedness::Pair {
+            Ok(pairs_between(high, Ace))
+        } else {
+            let top = Rank::ALL[Rank::ALL.iter().position(|rank| *rank == high).unwrap() - 1];
+            Ok(kickers_between(high, low, top, suitedness))
+        }
+    } else if item.len() >= 4 && item.len() % 2 == 0 {
+        let cards = (0..item.len())
+            .step_by(2)
+            .map(|i| item[i..i + 2].parse())
+            .collect::<Result<Vec<Card>, _>>()?;
+        if !cards.iter().all_unique() {
+            return Err(RangeParseError::InvalidHand(item.into()));
+        }
+        Ok(Range::from(cards))
+    } else {
+        let (high, low, suitedness) = parse_hand_class(item)?;
+        Ok(kickers_between(high, low, low, suitedness))
+    }
+}
+
+fn parse_hand_class(class: &str) -> Result<(Rank, Rank, Suitedness), RangeParseError> {
+    let invalid = || RangeParseError::InvalidHand(class.into());
+    if !(2..=3).contains(&class.len()) {
+        return Err(invalid());
+    }
+    let rank1: Rank = class[0..1].parse()?;
+    let rank2: Rank = class[1..2].parse()?;
+    let suitedness = match (&class[2..], rank1 == rank2) {
+        ("", true) => Suitedness::Pair,
+        ("", false) => Suitedness::Any,
+        ("s", false) => Suitedness::Suited,
+        ("o", false) => Suitedness::Offsuit,
+        _ => return Err(invalid()),
+    };
+    Ok((rank1.max(rank2), rank1.min(rank2), suitedness))
+}
+
+fn pairs_between(low: Rank, high: Rank) -> Range {
+    Rank::ALL
+        .into_iter()
+        .filter(|rank| (low..=high).contains(rank))
+        .map(Range::pair)
+        .fold(Range::new([]), Range::union)
+}
+
+fn kickers_between(high: Rank, low: Rank, top: Rank, suitedness: Suitedness) -> Range {
+    Rank::ALL
+        .into_iter()
+        .filter(|rank| (low..=top).contains(rank))
+        .map(|kicker| match suitedness {
+            Suitedness::Pair => Range::pair(high),
+            Suitedness::Suited => Range::suited(high, kicker),
+            Suitedness::Offsuit => Range::offsuit(high, kicker),
+            Suitedness::Any => Range::suited(high, kicker).union(Range::offsuit(high, kicker)),
+        })
+        .fold(Range::new([]), Range::union)
+}
+
+#[derive(Debug)]
+pub enum RangeParseError {
+    EmptyRange,
+    InvalidCard(CardParseError),
+    InvalidHand(String),
+    InvalidSpan(String),
+    InvalidWeight(String),
+}
+
+impl From<CardParseError> for RangeParseError {
+    fn from(error: CardParseError) -> Self {
+        RangeParseError::InvalidCard(error)
+    }
+}
+
+impl Error for RangeParseError {}
+
+impl Display for RangeParseError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            RangeParseError::EmptyRange => write!(fmt, "empty range"),
+            RangeParseError::InvalidCard(e) => write!(fmt, "{}", e),
+            RangeParseError::InvalidHand(s) => write!(fmt, "invalid hand: {}", s),
+            RangeParseError::InvalidSpan(s) => write!(fmt, "invalid span: {}", s),
+            RangeParseError::InvalidWeight(s) => write!(fmt, "invalid weight: {}", s),
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = VariantParseError;
+
+    fn from_str(string: &str) -> Result<Variant, VariantParseError> {
+        match string.to_ascii_lowercase().as_str() {
+            "holdem" | "nlhe" => Ok(Variant::Holdem),
+            "omaha" | "plo" | "plo4" => Ok(Variant::Omaha),
+            "omaha5" | "plo5" => Ok(Variant::Omaha5),
+            "omaha8" | "plo8" | "omaha-hilo" => Ok(Variant::OmahaHiLo),
+            "omaha5-8" | "plo5-8" | "omaha5-hilo" => Ok(Variant::Omaha5HiLo),
+            "stud8" | "stud-hilo" => Ok(Variant::Stud8),
+            "shortdeck" | "short-deck" | "6+" => Ok(Variant::ShortDeck),
+            _ => Err(VariantParseError(string.into())),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct VariantParseError(String);
+
+impl Error for VariantParseError {}
+
+impl Display for VariantParseError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(fmt, "unknown variant: {}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combos(range: &str) -> usize {
+        range.parse::<Range>().unwrap().len()
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(6, combos("AA"));
+        assert_eq!(18, combos("QQ+"));
+        assert_eq!(24, combos("55-22"));
+        assert_eq!(4, combos("AKs"));
+        assert_eq!(12, combos("KQo"));
+        assert_eq!(16, combos("AK"));
+        assert_eq!(16, combos("A5s-A2s"));
+        assert_eq!(16, combos("A2s-A5s"));
+        assert_eq!(12, combos("KTs+"));
+        assert_eq!(1, combos("AsKs"));
+        assert_eq!(1, combos("AsKsQdJd"));
+        assert_eq!(18 + 4 + 16 + 12, combos("QQ+, AKs, A5s-A2s, KQo"));
+        assert_eq!(6, combos("AA, AsAh"));
+        assert_eq!(
+            1326,
+            combos("22+, A2+, K2+, Q2+, J2+, T2+, 92+, 82+, 72+, 62+, 52+, 42+, 32")
+        );
+    }
+
+    #[test]
+    fn test_parse_weighted_range() {
+        let range: Range = "AKs:0.5, 22:0.25, QQ+:1".parse().unwrap();
+        assert_eq!(2.0 + 1.5 + 18.0, range.weight());
+        let range: Range = "JJ+, KK:0.5".parse().unwrap();
+        assert_eq!(6.0 + 6.0 + 3.0 + 6.0, range.weight());
+    }
+
+    #[test]
+    fn test_parse_invalid_range() {
+        for invalid in [
+            "", "AAs", "AKx", "A", "AKQs", "AsAs", "A5s-K2s", "A5s-A2o", "1A", "A♠K♠", "AsKsAs",
+        ] {
+            assert!(
+                invalid.parse::<Range>().is_err(),
+                "{} should be invalid",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_variant() {
+        assert_eq!(Variant::Holdem, "holdem".parse().unwrap());
+        assert_eq!(Variant::Omaha, "PLO".parse().unwrap());
+        assert_eq!(Variant::Omaha5, "plo5".parse().unwrap());
+        assert_eq!(Variant::OmahaHiLo, "omaha8".parse().unwrap());
+        assert_eq!(Variant::Stud8, "stud8".parse().unwrap());
+        assert_eq!(Variant::ShortDeck, "6+".parse().unwrap());
+        assert!("razz".parse::<Variant>().is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use itertools::Itertools;
+
+/// The main pot or a side pot, and the players who can win it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SidePot {
+    pub amount: f64,
+    pub eligible: Vec<usize>,
+}
+
+/// Splits the chips each player still in has put in into the main pot, first, and side pots.
+pub fn side_pots(contributions: &[f64]) -> Vec<SidePot> {
+    let mut previous = 0.0;
+    contributions
+        .iter()
+        .copied()
+        .filter(|amount| *amount > 0.0)
+        .sorted_by(f64::total_cmp)
+        .dedup()
+        .map(|level| {
+            let amount = contributions
+                .iter()
+                .map(|contribution| contribution.min(level) - contribution.min(previous))
+                .sum();
+            let eligible = contributions
+                .iter()
+                .positions(|contribution| *contribution >= level)
+                .collect();
+            previous = level;
+            SidePot { amount, eligible }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_pots() {
+        assert_eq!(
+            vec![
+                SidePot {
+                    amount: 150.0,
+                    eligible: vec![0, 1, 2],
+                },
+                SidePot {
+                    amount: 100.0,
+                    eligible: vec![0, 2],
+                },
+                // the bigger stack's uncalled chips
+                SidePot {
+                    amount: 50.0,
+                    eligible: vec![2],
+                },
+            ],
+            side_pots(&[100.0, 50.0, 150.0])
+        );
+        assert_eq!(1, side_pots(&[20.0, 20.0]).len());
+        assert!(side_pots(&[]).is_empty());
+    }
+}
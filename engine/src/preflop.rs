@@ -0,0 +1,448 @@
+use crate::calc::{HandOdds, Odds, Spot, StoppingRule};
+use crate::card::{Cards, Rank};
+use crate::floyd::SplitRng;
+use crate::game::{Game, GameState};
+use crate::range::Range;
+use crate::variant::Variant;
+use rayon::prelude::*;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+use std::iter::{once, repeat};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The largest number of random opponents the preflop table covers, i.e. a ten-handed game.
+pub const MAX_OPPONENTS: usize = 9;
+
+const MAGIC: &[u8; 8] = b"oddpre\0\x02";
+
+const HEADS_UP_LEN: usize = StartingHand::COUNT * (StartingHand::COUNT + 1) / 2;
+
+/// One of the 169 classes of hold'em starting hands, e.g. `AA`, `AKs` or `AKo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StartingHand {
+    pub high: Rank,
+    pub low: Rank,
+    pub suited: bool,
+}
+
+impl StartingHand {
+    pub const COUNT: usize = 169;
+
+    /// In grid order: aces top left, pairs on the diagonal and suited hands above it.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..Self::COUNT).map(Self::from_index)
+    }
+
+    pub fn index(self) -> usize {
+        let (high, low) = (grid_index(self.high), grid_index(self.low));
+        match self.suited {
+            true => high * 13 + low,
+            false => low * 13 + high,
+        }
+    }
+
+    fn from_index(index: usize) -> Self {
+        let (row, column) = (index / 13, index % 13);
+        let rank = |i: usize| Rank::ALL[12 - i];
+        StartingHand {
+            high: rank(row.min(column)),
+            low: rank(row.max(column)),
+            suited: row < column,
+        }
+    }
+
+    pub fn of(hole_cards: &Cards) -> Option<Self> {
+        match hole_cards {
+            [a, b] => Some(StartingHand {
+                high: a.rank.max(b.rank),
+                low: a.rank.min(b.rank),
+                suited: a.suit == b.suit && a.rank != b.rank,
+            }),
+            _ => None,
+        }
+    }
+
+    fn of_range(range: &Range) -> Option<Self> {
+        if let Some(hole_cards) = range.fixed() {
+            return Self::of(hole_cards);
+        }
+        let hand = Self::of(range.combos().first()?)?;
+        (!range.is_weighted() && *range == hand.range()).then_some(hand)
+    }
+
+    pub fn range(self) -> Range {
+        match (self.high == self.low, self.suited) {
+            (true, _) => Range::pair(self.high),
+            (false, true) => Range::suited(self.high, self.low),
+            (false, false) => Range::offsuit(self.high, self.low),
+        }
+    }
+}
+
+fn grid_index(rank: Rank) -> usize {
+    Rank::Ace as usize - rank as usize
+}
+
+/// How often a hand wins outright and ties, and its equity, as fractions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Rates {
+    win: f64,
+    tie: f64,
+    equity: f64,
+    /// The mean square of the pot share, for the equity's standard error.
+    equity_squared: f64,
+}
+
+impl Rates {
+    fn of(odds: &HandOdds) -> Self {
+        Rates {
+            win: odds.win_percent() / 100.0,
+            tie: odds.tie_percent() / 100.0,
+            equity: odds.equity(),
+            equity_squared: odds.equity_squared(),
+        }
+    }
+
+    fn swapped(self) -> Self {
+        Rates {
+            win: (1.0 - self.win - self.tie).max(0.0),
+            tie: self.tie,
+            equity: 1.0 - self.equity,
+            equity_squared: 1.0 - 2.0 * self.equity + self.equity_squared,
+        }
+    }
+
+    fn hand_odds(self, id: u64, samples: u64) -> HandOdds {
+        HandOdds::from_rates(
+            id,
+            self.win,
+            self.tie,
+            self.equity,
+            self.equity_squared,
+            samples,
+        )
+    }
+
+    fn write(self, writer: &mut impl Write) -> io::Result<()> {
+        for rate in [self.win, self.tie, self.equity, self.equity_squared] {
+            let fixed = (rate.clamp(0.0, 1.0) * u16::MAX as f64).round() as u16;
+            writer.write_all(&fixed.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut rate = || {
+            let mut bytes = [0; 2];
+            reader.read_exact(&mut bytes)?;
+            Ok::<_, io::Error>(u16::from_le_bytes(bytes) as f64 / u16::MAX as f64)
+        };
+        Ok(Rates {
+            win: rate()?,
+            tie: rate()?,
+            equity: rate()?,
+            equity_squared: rate()?,
+        })
+    }
+}
+
+/// Preflop hold'em odds of each starting hand heads-up and against random opponents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreflopTable {
+    samples: u64,
+    /// Each pair of starting hands once, in grid order.
+    heads_up: Vec<Rates>,
+    /// Against each number of random opponents, along with an average opponent's odds.
+    multiway: Vec<(Rates, Rates)>,
+}
+
+#[derive(Debug)]
+pub enum PreflopTableError {
+    Io(io::Error),
+    InvalidFormat,
+}
+
+impl From<io::Error> for PreflopTableError {
+    fn from(error: io::Error) -> Self {
+        PreflopTableError::Io(error)
+    }
+}
+
+impl Display for PreflopTableError {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            PreflopTableError::Io(error) => write!(fmt, "{}", error),
+            PreflopTableError::InvalidFormat => write!(fmt, "not a preflop table"),
+        }
+    }
+}
+
+impl Error for PreflopTableError {}
+
+impl PreflopTable {
+    /// Estimates each entry from `samples` scenarios, telling `observer` how many are done.
+    pub fn generate<R: SplitRng + Sync>(
+        samples: usize,
+        rng: &R,
+        observer: impl Fn(usize, usize) + Sync,
+    ) -> Self {
+        let total = HEADS_UP_LEN + StartingHand::COUNT * MAX_OPPONENTS;
+        let done = AtomicUsize::new(0);
+        let play = |players: Vec<Range>, opponents: usize, stream: usize| {
+            let game = Game::new(players, vec![], opponents, 0);
+            let outcome = game
+                .play(rng.stream(stream as u64), StoppingRule::samples(samples))
+                .expect("bug: every preflop spot is valid");
+            observer(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+            match outcome.state {
+                GameState::Undecided(odds) => odds.merge_unknown_players(1),
+                GameState::GameOver(_) => unreachable!("bug: the board is empty"),
+            }
+        };
+        let pairs = (0..StartingHand::COUNT)
+            .flat_map(|i| (i..StartingHand::COUNT).map(move |j| (i, j)))
+            .collect::<Vec<_>>();
+        let heads_up = pairs
+            .into_par_iter()
+            .enumerate()
+            .map(|(stream, (i, j))| {
+                let players = [i, j].map(|k| StartingHand::from_index(k).range());
+                Rates::of(play(players.to_vec(), 0, stream).iter().next().unwrap())
+            })
+            .collect();
+        let multiway = (0..StartingHand::COUNT * MAX_OPPONENTS)
+            .into_par_iter()
+            .map(|k| {
+                let hand = StartingHand::from_index(k / MAX_OPPONENTS);
+                let odds = play(vec![hand.range()], k % MAX_OPPONENTS + 1, HEADS_UP_LEN + k);
+                let mut odds = odds.iter().map(Rates::of);
+                (odds.next().unwrap(), odds.next().unwrap())
+            })
+            .collect();
+        PreflopTable {
+            samples: samples as u64,
+            heads_up,
+            multiway,
+        }
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.samples.to_le_bytes())?;
+        let multiway = self
+            .multiway
+            .iter()
+            .flat_map(|(hand, opponent)| [hand, opponent]);
+        for rates in self.heads_up.iter().chain(multiway) {
+            rates.write(&mut writer)?;
+        }
+        writer.flush()
+    }
+
+    pub fn read(mut reader: impl Read) -> Result<Self, PreflopTableError> {
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(PreflopTableError::InvalidFormat);
+        }
+        let mut samples = [0; 8];
+        reader.read_exact(&mut samples)?;
+        let heads_up = (0..HEADS_UP_LEN)
+            .map(|_| Rates::read(&mut reader))
+            .collect::<io::Result<_>>()?;
+        let multiway = (0..StartingHand::COUNT * MAX_OPPONENTS)
+            .map(|_| Ok((Rates::read(&mut reader)?, Rates::read(&mut reader)?)))
+            .collect::<io::Result<_>>()?;
+        if reader.read(&mut [0])? != 0 {
+            return Err(PreflopTableError::InvalidFormat);
+        }
+        Ok(PreflopTable {
+            samples: u64::from_le_bytes(samples),
+            heads_up,
+            multiway,
+        })
+    }
+
+    /// The odds of a preflop hold'em spot, if the table covers it.
+    pub(crate) fn lookup(&self, spot: Spot) -> Option<Odds> {
+        if spot.variant != Variant::Holdem
+            || !spot.board.is_empty()
+            || spot.n_folded > 0
+            || spot.streets
+            || !spot.pots.is_empty()
+        {
+            return None;
+        }
+        let hands = spot
+            .players
+            .iter()
+            .map(StartingHand::of_range)
+            .collect::<Option<Vec<_>>>()?;
+        let rates = match (&hands[..], spot.opponents) {
+            // the table averages over suits, so one player must hold every combo of their hand
+            ([hero, villain], 0) if spot.players.iter().any(|range| range.fixed().is_none()) => {
+                let rates = self.heads_up(*hero, *villain);
+                vec![rates, rates.swapped()]
+            }
+            ([hand], n) if (1..=MAX_OPPONENTS).contains(&n) => {
+                let (hero, opponent) = self.multiway[hand.index() * MAX_OPPONENTS + n - 1];
+                once(hero).chain(repeat(opponent).take(n)).collect()
+            }
+            _ => return None,
+        };
+        let odds = rates
+            .into_iter()
+            .enumerate()
+            .map(|(id, rates)| rates.hand_odds(id as u64, self.samples));
+        Some(Odds::from_hands(odds.collect()))
+    }
+
+    fn heads_up(&self, hero: StartingHand, villain: StartingHand) -> Rates {
+        let (i, j) = (hero.index(), villain.index());
+        // the pairs before row `i` of the upper triangle, then the position within it
+        let index = |i: usize, j: usize| i * (2 * StartingHand::COUNT - i + 1) / 2 + j - i;
+        match i <= j {
+            true => self.heads_up[index(i, j)],
+            false => self.heads_up[index(j, i)].swapped(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+    use crate::game::Method;
+    use crate::Player;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_starting_hands() {
+        let all = StartingHand::all().collect::<Vec<_>>();
+        assert_eq!(169, all.len());
+        for (i, hand) in all.iter().enumerate() {
+            assert_eq!(i, hand.index());
+        }
+        let ak = StartingHand::of(&parse_cards("Kh Ah")).unwrap();
+        assert_eq!("AKs", ak.to_string());
+        assert_eq!(1, ak.index());
+        assert_eq!(
+            "AKo",
+            StartingHand::of(&parse_cards("Kh As")).unwrap().to_string()
+        );
+        assert_eq!("AA", all[0].to_string());
+        assert_eq!("22", all[168].to_string());
+        assert_eq!(78, all.iter().filter(|hand| hand.suited).count());
+        let range = "AKs".parse::<Range>().unwrap();
+        assert_eq!(Some(ak), StartingHand::of_range(&range));
+        assert_eq!(None, StartingHand::of_range(&"AKs, AA".parse().unwrap()));
+    }
+
+    fn table() -> PreflopTable {
+        let rates = |k: usize, n: usize| {
+            let equity = 0.95 * k as f64 / n as f64;
+            Rates {
+                win: 0.9 * k as f64 / n as f64,
+                tie: 0.1,
+                equity,
+                // as if some of the pots were split
+                equity_squared: equity * (equity + 1.0) / 2.0,
+            }
+        };
+        let multiway_len = StartingHand::COUNT * MAX_OPPONENTS;
+        PreflopTable {
+            samples: 1000,
+            heads_up: (0..HEADS_UP_LEN).map(|k| rates(k, HEADS_UP_LEN)).collect(),
+            multiway: (0..multiway_len)
+                .map(|k| {
+                    (
+                        rates(k, multiway_len),
+                        rates(multiway_len - k, multiway_len),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_read_and_write() {
+        let table = table();
+        let mut bytes = vec![];
+        table.write(&mut bytes).unwrap();
+        assert_eq!(
+            8 + 8 + 8 * (HEADS_UP_LEN + 2 * StartingHand::COUNT * MAX_OPPONENTS),
+            bytes.len()
+        );
+        let read = PreflopTable::read(&bytes[..]).unwrap();
+        assert_eq!(table.samples, read.samples);
+        for (a, b) in table.heads_up.iter().zip(&read.heads_up) {
+            assert!((a.equity - b.equity).abs() < 1e-4);
+        }
+        assert!(matches!(
+            PreflopTable::read(&bytes[1..]),
+            Err(PreflopTableError::InvalidFormat)
+        ));
+        assert!(matches!(
+            PreflopTable::read(&bytes[..100]),
+            Err(PreflopTableError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let table = Arc::new(table());
+        let rng = fastrand::Rng::with_seed(1);
+        let aces = Range::from(parse_cards("Ah As"));
+        let game = Game::new(vec![aces.clone(), "KK".parse().unwrap()], vec![], 0, 0)
+            .with_preflop_table(table.clone());
+        let outcome = game.play(rng.clone(), StoppingRule::samples(1)).unwrap();
+        assert_eq!(Method::Lookup, outcome.method);
+        assert_eq!(1000, outcome.samples);
+        let GameState::Undecided(odds) = outcome.state else {
+            panic!("the game should be undecided");
+        };
+        let equity = odds.iter().map(HandOdds::equity).collect::<Vec<_>>();
+        let (aa, kk) = (starting_hand("AA"), starting_hand("KK"));
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(table.heads_up(aa, kk).equity, equity[0]));
+        assert!(close(1.0 - table.heads_up(kk, aa).equity, equity[0]));
+        assert!(close(1.0 - equity[0], equity[1]));
+        // the standard error follows from the mean square of the pot share, not just the equity
+        let hero = table.heads_up(aa, kk);
+        let variance = hero.equity_squared - hero.equity * hero.equity;
+        let standard_error = odds.iter().next().unwrap().equity_standard_error();
+        assert!(close(100.0 * (variance / 1000.0).sqrt(), standard_error));
+
+        // the table only holds averages over the suits of both players' hands
+        let kings = Range::from(parse_cards("Kh Kd"));
+        let spot = Spot {
+            variant: Variant::Holdem,
+            players: &[aces.clone(), kings],
+            board: &[],
+            opponents: 0,
+            n_folded: 0,
+            streets: false,
+            pots: &[],
+        };
+        assert!(table.lookup(spot).is_none());
+
+        let game = Game::new(vec![aces], vec![], 3, 0).with_preflop_table(table.clone());
+        let GameState::Undecided(odds) = game.play(rng, StoppingRule::samples(1)).unwrap().state
+        else {
+            panic!("the game should be undecided");
+        };
+        let odds = odds
+            .merge_unknown_players(1)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let (hero, opponent) = table.multiway[2];
+        assert!(close(hero.equity, odds[0].equity()));
+        assert!(close(opponent.equity, odds[1].equity()));
+        assert!(matches!(odds[1].who, Player::Multiple(3)));
+    }
+
+    fn starting_hand(hand: &str) -> StartingHand {
+        StartingHand::of_range(&hand.parse().unwrap()).unwrap()
+    }
+}
@@ -0,0 +1,224 @@
+use crate::card::{Card, HoleCards, Rank, Suit};
+use crate::deck::Deck;
+use crate::floyd::Rng;
+use itertools::Itertools;
+use std::cmp::Reverse;
+use std::iter::zip;
+
+/// Possible hole cards for a player, each combo weighted by how often it's held.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Range {
+    combos: Vec<HoleCards>,
+    weights: Vec<f64>,
+    cumulative: Vec<f64>,
+}
+
+impl Range {
+    pub fn new(combos: impl IntoIterator<Item = HoleCards>) -> Self {
+        Self::with_weights(combos.into_iter().map(|combo| (combo, 1.0)))
+    }
+
+    /// When a combo is given more than once, the last weight wins.
+    pub fn with_weights(combos: impl IntoIterator<Item = (HoleCards, f64)>) -> Self {
+        let (combos, weights): (Vec<_>, Vec<_>) = combos
+            .into_iter()
+            .map(|(mut combo, weight)| {
+                combo.sort_by_key(|card| Reverse(key(card)));
+                (combo, weight)
+            })
+            .collect_vec()
+            .into_iter()
+            .rev()
+            .sorted_by_key(|(combo, _)| Reverse(combo.iter().map(key).collect_vec()))
+            .dedup_by(|(combo1, _), (combo2, _)| combo1 == combo2)
+            .filter(|(_, weight)| *weight > 0.0)
+            .unzip();
+        let cumulative = weights
+            .iter()
+            .scan(0.0, |total, weight| {
+                *total += weight;
+                Some(*total)
+            })
+            .collect();
+        Self {
+            combos,
+            weights,
+            cumulative,
+        }
+    }
+
+    pub fn pair(rank: Rank) -> Self {
+        Self::new(
+            Suit::ALL
+                .into_iter()
+                .tuple_combinations()
+                .map(|(suit1, suit2)| vec![Card { rank, suit: suit1 }, Card { rank, suit: suit2 }]),
+        )
+    }
+
+    pub fn suited(rank1: Rank, rank2: Rank) -> Self {
+        Self::new(
+            Suit::ALL
+                .into_iter()
+                .map(|suit| vec![Card { rank: rank1, suit }, Card { rank: rank2, suit }]),
+        )
+    }
+
+    pub fn offsuit(rank1: Rank, rank2: Rank) -> Self {
+        Self::new(
+            Suit::ALL
+                .into_iter()
+                .cartesian_product(Suit::ALL)
+                .filter(|(suit1, suit2)| suit1 != suit2)
+                .map(|(suit1, suit2)| {
+                    vec![
+                        Card {
+                            rank: rank1,
+                            suit: suit1,
+                        },
+                        Card {
+                            rank: rank2,
+                            suit: suit2,
+                        },
+                    ]
+                }),
+        )
+    }
+
+    pub fn weighted(self, weight: f64) -> Self {
+        Self::with_weights(self.into_weighted().map(|(combo, w)| (combo, w * weight)))
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::with_weights(self.into_weighted().chain(other.into_weighted()))
+    }
+
+    pub fn live(&self, deck: &Deck) -> Self {
+        Self::with_weights(
+            zip(&self.combos, &self.weights)
+                .filter(|(combo, _)| combo.iter().all(|card| deck.contains(card)))
+                .map(|(combo, weight)| (combo.clone(), *weight)),
+        )
+    }
+
+    /// Picks a combo with probability proportional to its weight.
+    pub fn sample(&self, rng: &mut impl Rng<usize>) -> &HoleCards {
+        let i = match (self.len(), self.is_weighted()) {
+            (1, _) => 0,
+            (n, false) => rng.generate(0..n),
+            (n, true) => {
+                // 53 random bits fill the mantissa of a uniformly distributed f64 in [0, 1)
+                let unit = rng.generate(0..1 << 53) as f64 / (1u64 << 53) as f64;
+                let x = unit * self.weight();
+                self.cumulative
+                    .partition_point(|total| *total <= x)
+                    .min(n - 1)
+            }
+        };
+        &self.combos[i]
+    }
+
+    pub fn combos(&self) -> &[HoleCards] {
+        &self.combos
+    }
+
+    pub fn weighted_combos(&self) -> impl Iterator<Item = (&HoleCards, f64)> {
+        zip(&self.combos, self.weights.iter().copied())
+    }
+
+    /// The sum of the combos' weights.
+    pub fn weight(&self) -> f64 {
+        self.cumulative.last().copied().unwrap_or(0.0)
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.weights.iter().any(|weight| *weight != 1.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    pub fn fixed(&self) -> Option<&HoleCards> {
+        match &self.combos[..] {
+            [combo] => Some(combo),
+            _ => None,
+        }
+    }
+
+    fn into_weighted(self) -> impl Iterator<Item = (HoleCards, f64)> {
+        zip(self.combos, self.weights)
+    }
+}
+
+impl From<HoleCards> for Range {
+    fn from(hole_cards: HoleCards) -> Self {
+        Self::new([hole_cards])
+    }
+}
+
+fn key(card: &Card) -> (Rank, u8) {
+    (card.rank, card.suit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Rank::*;
+
+    #[test]
+    fn test_combo_counts() {
+        assert_eq!(6, Range::pair(Ace).len());
+        assert_eq!(4, Range::suited(Ace, King).len());
+        assert_eq!(12, Range::offsuit(Ace, King).len());
+        assert_eq!(
+            16,
+            Range::suited(Ace, King)
+                .union(Range::offsuit(King, Ace))
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_live_combos() {
+        let mut deck = Deck::default();
+        deck.remove(&"As".parse().unwrap()).unwrap();
+        deck.remove(&"Kh".parse().unwrap()).unwrap();
+        assert_eq!(3, Range::pair(Ace).live(&deck).len());
+        assert_eq!(2, Range::suited(Ace, King).live(&deck).len());
+        let weighted = Range::pair(Ace).union(Range::suited(Ace, King).weighted(0.5));
+        assert_eq!(4.0, weighted.live(&deck).weight());
+    }
+
+    #[test]
+    fn test_weighted_sampling() {
+        let range = Range::pair(Ace).weighted(0.25).union(Range::pair(King));
+        let mut rng = fastrand::Rng::with_seed(1);
+        let n = 100_000;
+        let aces = (0..n)
+            .filter(|_| range.sample(&mut rng)[0].rank == Ace)
+            .count();
+        let expected = n as f64 * 1.5 / 7.5;
+        assert!((aces as f64 - expected).abs() < 0.02 * expected, "{}", aces);
+    }
+
+    #[test]
+    fn test_last_weight_wins() {
+        let range = Range::pair(Ace).union(Range::pair(Ace).weighted(0.5));
+        assert_eq!(3.0, range.weight());
+        assert!(range.is_weighted());
+    }
+
+    #[test]
+    fn test_duplicate_combos_are_merged() {
+        let combo: HoleCards = vec!["Ah".parse().unwrap(), "Ad".parse().unwrap()];
+        let reversed = vec![combo[1], combo[0]];
+        let range = Range::pair(Ace).union(Range::from(reversed.clone()));
+        assert_eq!(6, range.len());
+        assert_eq!(Range::from(combo), Range::from(reversed));
+    }
+}
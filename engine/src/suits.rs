@@ -0,0 +1,155 @@
+use crate::card::{mask, Card, Cards, Suit, RANKS};
+use crate::range::Range;
+use itertools::Itertools;
+use std::iter::once;
+
+/// A relabelling of the four suits, which leaves the odds of any game unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SuitPermutation([Suit; 4]);
+
+impl SuitPermutation {
+    /// Every permutation of the suits, the identity first.
+    pub fn all() -> impl Iterator<Item = Self> {
+        Suit::ALL
+            .into_iter()
+            .permutations(Suit::ALL.len())
+            .map(|suits| SuitPermutation(suits.try_into().unwrap()))
+    }
+
+    pub fn apply(self, card: Card) -> Card {
+        Card {
+            suit: self.0[card.suit as usize],
+            ..card
+        }
+    }
+
+    pub fn apply_mask(self, mask: u64) -> u64 {
+        Suit::ALL.into_iter().fold(0, |permuted, suit| {
+            let cards = mask >> (suit as u32 * RANKS) & ((1 << RANKS) - 1);
+            permuted | cards << (self.0[suit as usize] as u32 * RANKS)
+        })
+    }
+
+    pub fn apply_range(self, range: &Range) -> Range {
+        Range::with_weights(range.weighted_combos().map(|(combo, weight)| {
+            let combo = combo.iter().map(|card| self.apply(*card)).collect();
+            (combo, weight)
+        }))
+    }
+
+    pub fn inverse(self) -> Self {
+        let mut inverse = Suit::ALL;
+        for suit in Suit::ALL {
+            inverse[self.0[suit as usize] as usize] = suit;
+        }
+        SuitPermutation(inverse)
+    }
+}
+
+/// The permutations that map the players' hole cards and the board onto themselves.
+pub fn symmetries(players: &[u64], board: u64) -> Vec<SuitPermutation> {
+    SuitPermutation::all()
+        .filter(|permutation| {
+            once(&board)
+                .chain(players)
+                .all(|cards| permutation.apply_mask(*cards) == *cards)
+        })
+        .collect()
+}
+
+/// Relabels the suits so that situations differing only by suits become the same.
+pub fn canonical(players: &[Range], board: &Cards) -> (Vec<Range>, Vec<Card>, SuitPermutation) {
+    SuitPermutation::all()
+        .map(|permutation| {
+            let players = players
+                .iter()
+                .map(|range| permutation.apply_range(range))
+                .collect_vec();
+            let board = board
+                .iter()
+                .map(|card| permutation.apply(*card))
+                .collect_vec();
+            (encode(&players, &board), players, board, permutation)
+        })
+        .min_by(|(a, ..), (b, ..)| a.cmp(b))
+        .map(|(_, players, board, permutation)| (players, board, permutation))
+        .unwrap()
+}
+
+/// Two situations have the same encoding exactly when they are the same.
+pub fn encode(players: &[Range], board: &Cards) -> Vec<u64> {
+    let players = players.iter().flat_map(|range| {
+        let combos = range
+            .weighted_combos()
+            .flat_map(|(combo, weight)| [mask(combo), weight.to_bits()]);
+        once(range.len() as u64).chain(combos)
+    });
+    once(mask(board)).chain(players).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn canonical(players: &[&str], board: &str) -> Vec<u64> {
+        let players = players
+            .iter()
+            .map(|cards| Range::from(parse_cards(cards)))
+            .collect_vec();
+        let board = match board {
+            "" => vec![],
+            board => parse_cards(board),
+        };
+        let (players, board, _) = super::canonical(&players, &board);
+        encode(&players, &board)
+    }
+
+    #[test]
+    fn test_permutations() {
+        let all = SuitPermutation::all().collect_vec();
+        assert_eq!(24, all.len());
+        assert!(all.iter().all_unique());
+        let cards = parse_cards("Ah Kc 2s 9d");
+        for permutation in all {
+            let permuted = cards
+                .iter()
+                .map(|card| permutation.apply(*card))
+                .collect_vec();
+            assert_eq!(mask(&permuted), permutation.apply_mask(mask(&cards)));
+            let inverse = permutation.inverse();
+            let restored = permuted
+                .iter()
+                .map(|card| inverse.apply(*card))
+                .collect_vec();
+            assert_eq!(cards, restored);
+        }
+    }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(
+            canonical(&["As Ks", "Qh Qd"], ""),
+            canonical(&["Ah Kh", "Qc Qs"], "")
+        );
+        assert_eq!(
+            canonical(&["As Ks", "Qh Qd"], "2s 3h 4c"),
+            canonical(&["Ad Kd", "Qc Qh"], "4s 2d 3c")
+        );
+        assert_ne!(
+            canonical(&["As Ks", "Qh Qd"], ""),
+            canonical(&["As Ks", "Qh Qs"], "")
+        );
+        let range = "AKs".parse::<Range>().unwrap();
+        let (players, ..) = super::canonical(&[range.clone()], &[]);
+        assert_eq!(vec![range], players);
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let players = [parse_cards("Ah Kh"), parse_cards("Qs Qc")].map(|cards| mask(&cards));
+        // swapping spades and clubs
+        assert_eq!(2, symmetries(&players, 0).len());
+        assert_eq!(24, symmetries(&[], 0).len());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::card::{Cards, Rank, Suit};
+use crate::variant::Variant;
+use itertools::Itertools;
+
+/// How a flop, turn or river board is made up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Texture {
+    pub pairing: Pairing,
+    pub suits: Suits,
+    pub connectedness: Connectedness,
+    pub high_card: HighCard,
+    /// The number of straights a player could hold using three or more cards from the board.
+    pub straights: usize,
+    /// The number of suits a player could make a flush in using three or more board cards.
+    pub flushes: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pairing {
+    Unpaired,
+    Paired,
+    TwoPair,
+    Trips,
+    FullHouse,
+    Quads,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Suits {
+    Rainbow,
+    TwoTone,
+    /// Three or more cards, but not all, share a suit.
+    ThreeFlush,
+    Monotone,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Connectedness {
+    Disconnected,
+    /// A straight is possible, but no three ranks are consecutive, e.g. 5-7-9.
+    Gapped,
+    /// Three or more ranks are consecutive, e.g. 7-8-9.
+    Connected,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HighCard {
+    /// Six or lower.
+    Low,
+    /// Seven to nine.
+    Middle,
+    /// Ten to king.
+    Broadway,
+    Ace,
+}
+
+/// Classifies a board of three or more cards; boards with fewer cards have no texture yet.
+pub fn texture(variant: Variant, board: &Cards) -> Option<Texture> {
+    if board.len() < 3 {
+        return None;
+    }
+    let mut rank_counts = board
+        .iter()
+        .map(|card| card.rank)
+        .counts()
+        .into_values()
+        .collect_vec();
+    rank_counts.sort_unstable_by(|a, b| b.cmp(a));
+    let pairing = match rank_counts[..] {
+        [4, ..] => Pairing::Quads,
+        [3, 2, ..] => Pairing::FullHouse,
+        [3, ..] => Pairing::Trips,
+        [2, 2, ..] => Pairing::TwoPair,
+        [2, ..] => Pairing::Paired,
+        _ => Pairing::Unpaired,
+    };
+
+    let suit_counts = Suit::ALL.map(|suit| board.iter().filter(|card| card.suit == suit).count());
+    let flushes = suit_counts.iter().filter(|n| **n >= 3).count();
+    let suits = match suit_counts.into_iter().max() {
+        Some(1) => Suits::Rainbow,
+        Some(2) => Suits::TwoTone,
+        Some(n) if n < board.len() => Suits::ThreeFlush,
+        _ => Suits::Monotone,
+    };
+
+    // whether the board holds each rank in the order they make straights, from the ace playing
+    // low to the ace playing high
+    let lowest = match variant {
+        Variant::ShortDeck => Rank::Six,
+        _ => Rank::Deuce,
+    };
+    let present = Rank::ALL_WITH_BOTH_ACES
+        .into_iter()
+        .filter(|rank| *rank == Rank::Ace || *rank >= lowest)
+        .map(|rank| board.iter().any(|card| card.rank == rank))
+        .collect_vec();
+    let straights = present
+        .windows(5)
+        .filter(|straight| straight.iter().filter(|present| **present).count() >= 3)
+        .count();
+    let connectedness = if present.windows(3).any(|ranks| ranks.iter().all(|r| *r)) {
+        Connectedness::Connected
+    } else if straights > 0 {
+        Connectedness::Gapped
+    } else {
+        Connectedness::Disconnected
+    };
+
+    let high_card = match board.iter().map(|card| card.rank).max() {
+        Some(Rank::Ace) => HighCard::Ace,
+        Some(rank) if rank >= Rank::Ten => HighCard::Broadway,
+        Some(rank) if rank >= Rank::Seven => HighCard::Middle,
+        _ => HighCard::Low,
+    };
+
+    Some(Texture {
+        pairing,
+        suits,
+        connectedness,
+        high_card,
+        straights,
+        flushes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::parse_cards;
+
+    fn texture(board: &str) -> Texture {
+        super::texture(Variant::Holdem, &parse_cards(board)).unwrap()
+    }
+
+    #[test]
+    fn test_texture() {
+        assert_eq!(
+            Texture {
+                pairing: Pairing::Unpaired,
+                suits: Suits::TwoTone,
+                connectedness: Connectedness::Connected,
+                high_card: HighCard::Middle,
+                straights: 3,
+                flushes: 0,
+            },
+            texture("7h 8h 9c")
+        );
+        assert_eq!(
+            Texture {
+                pairing: Pairing::Paired,
+                suits: Suits::ThreeFlush,
+                connectedness: Connectedness::Disconnected,
+                high_card: HighCard::Ace,
+                straights: 0,
+                flushes: 1,
+            },
+            texture("Ah As 2h 7h")
+        );
+        assert_eq!(
+            Texture {
+                pairing: Pairing::Unpaired,
+                suits: Suits::Rainbow,
+                connectedness: Connectedness::Gapped,
+                high_card: HighCard::Low,
+                straights: 1,
+                flushes: 0,
+            },
+            texture("2c 4d 6h")
+        );
+        let suits = |board| texture(board).suits;
+        assert_eq!(Suits::Monotone, suits("2h 7h Kh"));
+        assert_eq!(Suits::Monotone, suits("2h 7h Kh 9h"));
+        assert_eq!(Suits::ThreeFlush, suits("2h 7h Kh 9h 4c"));
+        assert_eq!(Suits::TwoTone, suits("2h 7h Kc 9c"));
+    }
+
+    #[test]
+    fn test_preflop_has_no_texture() {
+        assert_eq!(None, super::texture(Variant::Holdem, &[]));
+    }
+}
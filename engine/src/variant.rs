@@ -0,0 +1,241 @@
+use crate::card::{Cards, Players};
+use crate::deck::Deck;
+use crate::eval::{evaluate, evaluate_low, evaluate_short_deck, HandRank, LowRank};
+use crate::hand::{hand, short_deck_hand, Hand};
+use itertools::Itertools;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Variant {
+    #[default]
+    Holdem,
+    Omaha,
+    Omaha5,
+    /// Omaha where the pot is split between the best high hand and the best eight-or-better low.
+    OmahaHiLo,
+    Omaha5HiLo,
+    /// Seven card stud with an eight-or-better low half: no board, the best five of seven cards.
+    Stud8,
+    /// Short-deck (6+) hold'em: A-6-7-8-9 is a straight and a flush beats a full house.
+    ShortDeck,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 7] = [
+        Variant::Holdem,
+        Variant::Omaha,
+        Variant::Omaha5,
+        Variant::OmahaHiLo,
+        Variant::Omaha5HiLo,
+        Variant::Stud8,
+        Variant::ShortDeck,
+    ];
+
+    pub fn hole_cards(self) -> usize {
+        match self {
+            Variant::Holdem | Variant::ShortDeck => 2,
+            Variant::Omaha | Variant::OmahaHiLo => 4,
+            Variant::Omaha5 | Variant::Omaha5HiLo => 5,
+            Variant::Stud8 => 7,
+        }
+    }
+
+    pub fn board_length(self) -> usize {
+        match self {
+            Variant::Stud8 => 0,
+            _ => 5,
+        }
+    }
+
+    /// The number of board cards after each street, e.g. the flop, turn and river in hold'em.
+    pub fn streets(self) -> &'static [usize] {
+        match self {
+            Variant::Stud8 => &[],
+            _ => &[3, 4, 5],
+        }
+    }
+
+    pub fn deck(self) -> Deck {
+        match self {
+            Variant::ShortDeck => Deck::short(),
+            _ => Deck::default(),
+        }
+    }
+
+    pub fn is_hi_lo(self) -> bool {
+        matches!(
+            self,
+            Variant::OmahaHiLo | Variant::Omaha5HiLo | Variant::Stud8
+        )
+    }
+
+    /// Whether hole cards are dealt one street at a time.
+    pub fn is_stud(self) -> bool {
+        self == Variant::Stud8
+    }
+
+    fn is_omaha(self) -> bool {
+        matches!(
+            self,
+            Variant::Omaha | Variant::Omaha5 | Variant::OmahaHiLo | Variant::Omaha5HiLo
+        )
+    }
+
+    pub fn evaluate(self, hole_cards: u64, board: u64) -> HandRank {
+        if self == Variant::ShortDeck {
+            return evaluate_short_deck(hole_cards | board);
+        }
+        if !self.is_omaha() {
+            return evaluate(hole_cards | board);
+        }
+        omaha_hands(hole_cards, board)
+            .map(evaluate)
+            .max()
+            .expect("bug: not enough cards to make a hand")
+    }
+
+    pub fn evaluate_low(self, hole_cards: u64, board: u64) -> Option<LowRank> {
+        if !self.is_hi_lo() {
+            return None;
+        }
+        if !self.is_omaha() {
+            return evaluate_low(hole_cards | board);
+        }
+        omaha_hands(hole_cards, board)
+            .filter_map(evaluate_low)
+            .max()
+    }
+
+    pub fn hand(self, hole_cards: &Cards, board: &Cards) -> Hand {
+        if self == Variant::ShortDeck {
+            return short_deck_hand([hole_cards, board].concat());
+        }
+        if !self.is_omaha() {
+            return hand([hole_cards, board].concat());
+        }
+        hole_cards
+            .iter()
+            .copied()
+            .combinations(2)
+            .cartesian_product(board.iter().copied().combinations(3))
+            .map(|(hole_cards, board)| hand([hole_cards, board].concat()))
+            .max()
+            .expect("bug: not enough cards to make a hand")
+    }
+
+    pub fn evaluate_all(self, players: &[u64], board: u64) -> Vec<HandRank> {
+        players
+            .iter()
+            .map(|hole_cards| self.evaluate(*hole_cards, board))
+            .collect()
+    }
+
+    pub fn evaluate_all_low(self, players: &[u64], board: u64) -> Option<Vec<Option<LowRank>>> {
+        self.is_hi_lo().then(|| {
+            players
+                .iter()
+                .map(|hole_cards| self.evaluate_low(*hole_cards, board))
+                .collect()
+        })
+    }
+
+    pub fn hands(self, players: &Players, board: &Cards) -> Vec<Hand> {
+        players
+            .iter()
+            .map(|hole_cards| self.hand(hole_cards, board))
+            .collect()
+    }
+}
+
+fn omaha_hands(hole_cards: u64, board: u64) -> impl Iterator<Item = u64> {
+    bits(hole_cards)
+        .tuple_combinations()
+        .cartesian_product(bits(board).tuple_combinations())
+        .map(|((hole1, hole2), (board1, board2, board3))| hole1 | hole2 | board1 | board2 | board3)
+}
+
+fn bits(mut cards: u64) -> impl Iterator<Item = u64> + Clone {
+    std::iter::from_fn(move || {
+        let bit = cards & cards.wrapping_neg();
+        cards ^= bit;
+        (bit != 0).then_some(bit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{mask, parse_cards};
+    use crate::hand::HandType::*;
+
+    #[test]
+    fn test_omaha_uses_exactly_two_hole_cards() {
+        // four hearts in hand and one on board: a flush in hold'em, but not in omaha
+        let hole_cards = parse_cards("Ah Kh Qh Jh");
+        let board = parse_cards("2h 7c 8d 9s 3c");
+        let rank = Variant::Omaha.evaluate(mask(&hole_cards), mask(&board));
+        assert_eq!(HighCard, rank.hand_type());
+        assert_eq!(
+            Flush,
+            Variant::Holdem
+                .evaluate(mask(&hole_cards), mask(&board))
+                .hand_type()
+        );
+    }
+
+    #[test]
+    fn test_omaha_uses_exactly_three_board_cards() {
+        // four to a straight on board plus one hole card isn't enough in omaha
+        let hole_cards = parse_cards("9c 2d 2s Kh");
+        let board = parse_cards("5h 6c 7d 8s Ad");
+        let hand = Variant::Omaha.hand(&hole_cards, &board);
+        assert_eq!(Pair, hand.hand_type);
+        let rank = Variant::Omaha.evaluate(mask(&hole_cards), mask(&board));
+        assert_eq!(Pair, rank.hand_type());
+    }
+
+    #[test]
+    fn test_five_card_omaha() {
+        let hole_cards = parse_cards("As Ks 7h 7d 2c");
+        let board = parse_cards("7s 8s 9s Td 7c");
+        let hand = Variant::Omaha5.hand(&hole_cards, &board);
+        assert_eq!(FourOfAKind, hand.hand_type);
+        let rank = Variant::Omaha5.evaluate(mask(&hole_cards), mask(&board));
+        assert_eq!(FourOfAKind, rank.hand_type());
+    }
+
+    #[test]
+    fn test_omaha_low_uses_exactly_two_hole_cards() {
+        let board = mask(&parse_cards("3c 5d 8h Kc Qd"));
+        // only one low card in hand
+        let hole_cards = mask(&parse_cards("Ah Kh Qh Jh"));
+        assert_eq!(None, Variant::OmahaHiLo.evaluate_low(hole_cards, board));
+        // three low cards in hand, but the board only has three low cards of its own
+        let hole_cards = mask(&parse_cards("Ah 2h 4c Jh"));
+        let low = Variant::OmahaHiLo.evaluate_low(hole_cards, board).unwrap();
+        let expected = evaluate_low(mask(&parse_cards("Ah 2h 3c 5d 8h")));
+        assert_eq!(expected, Some(low));
+        assert_eq!(None, Variant::Omaha.evaluate_low(hole_cards, board));
+    }
+
+    #[test]
+    fn test_stud_low_uses_any_five_cards() {
+        let cards = mask(&parse_cards("Ah 2h 4c Jh 9s 7d 6c"));
+        let low = Variant::Stud8.evaluate_low(cards, 0).unwrap();
+        assert_eq!(
+            evaluate_low(mask(&parse_cards("7d 6c 4c 2h Ah"))),
+            Some(low)
+        );
+        assert_eq!(HighCard, Variant::Stud8.evaluate(cards, 0).hand_type());
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let hole_cards = parse_cards("As 6d");
+        let board = parse_cards("7h 8c 9s Kd Kh");
+        let rank = Variant::ShortDeck.evaluate(mask(&hole_cards), mask(&board));
+        assert_eq!(Straight, rank.hand_type());
+        let hand = Variant::ShortDeck.hand(&hole_cards, &board);
+        assert_eq!(parse_cards("9s 8c 7h 6d As"), hand.cards.to_vec());
+        assert_eq!(36, Variant::ShortDeck.deck().len());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Keeps the values most recently looked up, each for a limited time.
+pub struct Cache<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    entries: HashMap<K, Entry<V>>,
+    recency: BTreeMap<u64, K>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    last_used: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Cache<K, V> {
+    /// A capacity of 0 disables the cache.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.clock += 1;
+        let clock = self.clock;
+        let value = match self.entries.get_mut(key) {
+            Some(entry) if entry.inserted.elapsed() < self.ttl => {
+                self.recency.remove(&entry.last_used);
+                self.recency.insert(clock, key.clone());
+                entry.last_used = clock;
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                self.remove(key);
+                None
+            }
+            None => None,
+        };
+        match value {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(&key);
+        while self.entries.len() >= self.capacity {
+            let (_, oldest) = self.recency.pop_first().unwrap();
+            self.entries.remove(&oldest);
+        }
+        self.clock += 1;
+        self.recency.insert(self.clock, key.clone());
+        let entry = Entry {
+            value,
+            inserted: Instant::now(),
+            last_used: self.clock,
+        };
+        self.entries.insert(key, entry);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+        }
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = Cache::new(2, Duration::from_secs(60));
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(Some(1), cache.get(&"a"));
+        cache.insert("c", 3);
+        assert_eq!(None, cache.get(&"b"));
+        assert_eq!(Some(1), cache.get(&"a"));
+        assert_eq!(Some(3), cache.get(&"c"));
+        assert_eq!(
+            CacheStats {
+                hits: 3,
+                misses: 1,
+                entries: 2
+            },
+            cache.stats()
+        );
+    }
+
+    #[test]
+    fn test_expires() {
+        let mut cache = Cache::new(2, Duration::ZERO);
+        cache.insert("a", 1);
+        assert_eq!(None, cache.get(&"a"));
+        assert_eq!(0, cache.stats().entries);
+
+        let mut disabled = Cache::new(0, Duration::from_secs(60));
+        disabled.insert("a", 1);
+        assert_eq!(None, disabled.get(&"a"));
+    }
+}
@@ -1,12 +1,29 @@
 #![forbid(unsafe_code)]
+mod cache;
+
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::task;
+use cache::{Cache, CacheStats};
 use fastrand::Rng;
 use mimalloc::MiMalloc;
 use odd_engine::{
-    Card, Game, GameOutcome, GameState, HandOutcome, Odds, Player, HOLE_CARDS_PER_PLAYER,
+    confidence_interval, Card, DrawDistribution, Effect, Ev, Game, GameError, GameKey, GameOutcome,
+    GameState, HandOutcome, Method, NextCards, Nuts, Odds, Player, Pots, PreflopTable, Range,
+    Stakes, StakesError, StopReason, StoppingRule, SuitPermutation, Texture, Variant,
 };
+use serde_json::Value;
 use serde_with::{serde_as, DisplayFromStr};
 use std::collections::HashMap;
 use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::iter::zip;
+use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tide::http::headers::HeaderValue;
 use tide::prelude::*;
 use tide::security::{CorsMiddleware, Origin};
@@ -18,62 +35,410 @@ static ALLOCATOR: MiMalloc = MiMalloc;
 #[serde_as]
 #[derive(Debug, Deserialize)]
 struct Input {
-    #[serde_as(as = "Vec<[DisplayFromStr; HOLE_CARDS_PER_PLAYER]>")]
-    players: Vec<[Card; HOLE_CARDS_PER_PLAYER]>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    variant: Option<Variant>,
+    players: Vec<Holding>,
     #[serde_as(as = "Vec<DisplayFromStr>")]
     board: Vec<Card>,
-    iterations: Option<usize>,
+    iterations: Option<NonZeroUsize>,
+    /// In percentage points of the first player's equity.
+    target_error: Option<f64>,
+    /// In milliseconds.
+    time_limit: Option<u64>,
+    #[serde(default)]
+    streets: bool,
     opponents: Option<usize>,
+    #[serde(default)]
+    opponent_ranges: Vec<Holding>,
     folded: Option<usize>,
+    top: Option<usize>,
+    /// Including any bet the first player faces.
+    pot: Option<f64>,
+    to_call: Option<f64>,
+    /// The smaller of the first player's stack and the bettor's.
+    stack: Option<f64>,
+    /// Known players first, then opponents with ranges, then any other opponents.
+    #[serde(default)]
+    contributions: Vec<f64>,
+}
+
+/// Either a player's hole cards, e.g. `["As", "Kh"]`, or a range, e.g. `"QQ+, AKs"`.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum Holding {
+    HoleCards(#[serde_as(as = "Vec<DisplayFromStr>")] Vec<Card>),
+    Range(#[serde_as(as = "DisplayFromStr")] Range),
+}
+
+impl From<Holding> for Range {
+    fn from(holding: Holding) -> Self {
+        match holding {
+            Holding::HoleCards(hole_cards) => Range::from(hole_cards),
+            Holding::Range(range) => range,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct State {
+    preflop_table: Option<Arc<PreflopTable>>,
+    cache: Arc<Mutex<Cache<CacheKey, Evaluation>>>,
+}
+
+/// An `/evaluate` response in the canonical suits.
+#[derive(Clone)]
+struct Evaluation {
+    result: Value,
+    hands: Vec<[Card; 5]>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    game: GameKey,
+    iterations: usize,
+    target_error: Option<u64>,
+    time_limit: u64,
+    pot: Option<u64>,
+    to_call: Option<u64>,
+    stack: Option<u64>,
 }
 
 #[async_std::main]
 async fn main() -> tide::Result<()> {
-    let mut app = tide::new();
+    let preflop_table = match env::var("ODD_PREFLOP_TABLE") {
+        Ok(path) => Some(Arc::new(PreflopTable::read(BufReader::new(File::open(
+            path,
+        )?))?)),
+        Err(_) => None,
+    };
+    let cache_size = env::var("ODD_CACHE_SIZE")
+        .map(|size| size.parse())
+        .unwrap_or(Ok(10_000))?;
+    let cache_ttl = env::var("ODD_CACHE_TTL")
+        .map(|seconds| seconds.parse())
+        .unwrap_or(Ok(3600))?;
+    let app = app(State {
+        preflop_table,
+        cache: Arc::new(Mutex::new(Cache::new(
+            cache_size,
+            Duration::from_secs(cache_ttl),
+        ))),
+    });
     let port = env::var("ODD_PORT")
         .map(|port| port.parse())
         .unwrap_or(Ok(8080))?;
+    serve(app, format!("localhost:{}", port)).await
+}
+
+fn app(state: State) -> tide::Server<State> {
+    let mut app = tide::with_state(state);
     let cors = CorsMiddleware::new()
         .allow_methods("GET, POST, OPTIONS".parse::<HeaderValue>().unwrap())
         .allow_origin(Origin::from("*"))
         .allow_credentials(false);
     app.with(cors);
     app.at("/evaluate").post(evaluate);
-    app.listen(format!("localhost:{}", port)).await?;
+    app.at("/next-card").post(next_card);
+    app.at("/nuts").post(nuts);
+    app.at("/cache").get(cache_stats);
+    app
+}
+
+/// Like `tide::Server::listen`, but drops a request's handler once its client disconnects.
+async fn serve(app: tide::Server<State>, addr: String) -> tide::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let app = app.clone();
+        task::spawn(async move {
+            let watched = stream.clone();
+            async_h1::accept(stream, |req| {
+                app.respond(req).race(async {
+                    disconnected(&watched).await;
+                    Err(tide::Error::from_str(
+                        tide::StatusCode::BadRequest,
+                        "client disconnected",
+                    ))
+                })
+            })
+            .await
+        });
+    }
     Ok(())
 }
 
-async fn evaluate(mut req: Request<()>) -> tide::Result<Body> {
-    let Input {
-        players,
-        board,
-        iterations,
-        opponents,
-        folded,
-    } = req.body_json().await?;
+async fn disconnected(mut stream: &TcpStream) {
+    loop {
+        match stream.peek(&mut [0]).await {
+            Err(_) => return,
+            // the client may only have closed its sending side, so probe with an interim response
+            // that a closed connection refuses
+            Ok(0) => {
+                if stream
+                    .write_all(b"HTTP/1.1 102 Processing\r\n\r\n")
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+                task::sleep(Duration::from_secs(1)).await
+            }
+            // the rest of the request or the next one has yet to be read
+            Ok(_) => task::sleep(Duration::from_millis(100)).await,
+        }
+    }
+}
+
+impl Input {
+    fn iterations(&self) -> usize {
+        self.iterations
+            .map_or(100_000, NonZeroUsize::get)
+            .min(1_000_000)
+    }
+
+    /// In milliseconds.
+    fn time_limit(&self) -> u64 {
+        self.time_limit.unwrap_or(10_000).min(10_000)
+    }
+
+    fn stakes(&self) -> Result<Option<Stakes>, StakesError> {
+        self.pot
+            .map(|pot| Stakes::new(pot, self.to_call.unwrap_or(0.0), self.stack))
+            .transpose()
+    }
+
+    fn stopping_rule(&self) -> StoppingRule {
+        let mut rule = StoppingRule::samples(self.iterations())
+            .with_time_limit(Duration::from_millis(self.time_limit()));
+        if let Some(standard_error) = self.target_error {
+            rule = rule.with_target_standard_error(standard_error);
+        }
+        rule
+    }
+
+    fn game(&self) -> Game {
+        let opponent_ranges = self.opponent_ranges.iter().take(8).cloned();
+        let opponent_ranges = opponent_ranges.map(Range::from).collect::<Vec<_>>();
+        let n_opponents = self.opponents.unwrap_or(0).min(8 - opponent_ranges.len());
+        let n_folded = self
+            .folded
+            .unwrap_or(0)
+            .min(8 - opponent_ranges.len() - n_opponents);
+        let players = self.players.iter().cloned().map(Range::from).collect();
+        Game::new(players, self.board.clone(), n_opponents, n_folded)
+            .with_variant(self.variant.unwrap_or_default())
+            .with_opponent_ranges(opponent_ranges)
+            .with_streets(self.streets)
+            .with_contributions(self.contributions.clone())
+    }
+}
+
+async fn evaluate(mut req: Request<State>) -> tide::Result<Body> {
+    let input: Input = req.body_json().await?;
     let rng = RngAdapter(Rng::with_seed(1));
-    let n_opponents = opponents.unwrap_or(0).min(8);
-    let n_folded = folded.unwrap_or(0).min(8 - n_opponents);
-    let n_players = players.len();
-    let game = Game::new(players, board, n_opponents, n_folded);
+    let rule = input.stopping_rule();
+    let n_players = input.players.len();
+    let variant = input.variant.unwrap_or_default();
+    let streets = input.streets;
+    let board_len = input.board.len();
+    // the streets still to come, including the river
+    let street_names = ["flop", "turn", "river"]
+        .into_iter()
+        .skip(input.board.len().saturating_sub(2))
+        .collect::<Vec<_>>();
+    let stakes = input.stakes()?;
+    let (mut game, permutation) = input.game().canonical();
+    let key = CacheKey {
+        game: game.key(),
+        iterations: input.iterations(),
+        target_error: input.target_error.map(f64::to_bits),
+        time_limit: input.time_limit(),
+        pot: input.pot.map(f64::to_bits),
+        to_call: input.to_call.map(f64::to_bits),
+        stack: input.stack.map(f64::to_bits),
+    };
+    let cache = req.state().cache.clone();
+    if let Some(evaluation) = cache.lock().unwrap().get(&key) {
+        return Body::from_json(&in_suits(evaluation, permutation.inverse()));
+    }
+    if let Some(table) = &req.state().preflop_table {
+        game = game.with_preflop_table(table.clone());
+    }
+    let draws = game.draws()?;
+    let texture = game.texture();
     let GameOutcome {
         state,
         cards_remaining,
-    } = game.play(rng, iterations.unwrap_or(100_000).min(1_000_000))?;
-    match state {
-        GameState::Undecided(odds) => format_odds(odds, cards_remaining, n_players),
-        GameState::GameOver(outcomes) => format_outcomes(outcomes, cards_remaining),
+        method,
+        live_combos,
+        samples,
+        stop_reason,
+        pots,
+    } = play(game, rng, rule).await?;
+    let mut hands = Vec::new();
+    let mut result = match state {
+        GameState::Undecided(odds) => {
+            let ev = stakes
+                .zip(odds.iter().next())
+                .map(|(stakes, hero)| hero.ev(stakes));
+            let mut result = json!({
+                "method": format_method(method),
+                "samples": samples,
+                "stop_reason": stop_reason.map(format_stop_reason),
+                "odds": format_odds(odds, n_players, variant, method, streets.then_some(&street_names)),
+            });
+            if let Some(ev) = ev {
+                result["ev"] = format_ev(ev);
+            }
+            result
+        }
+        GameState::GameOver(outcomes) => {
+            hands = outcomes.iter().map(|outcome| outcome.hand.cards).collect();
+            json!({
+                "outcomes": format_outcomes(outcomes),
+            })
+        }
+    };
+    result["cards_remaining"] = json!(cards_remaining);
+    result["live_combos"] = json!(live_combos);
+    if let Some(pots) = pots {
+        result["pots"] = format_pots(pots);
+    }
+    if let Some(texture) = texture {
+        result["texture"] = format_texture(texture);
+    }
+    if let Some(draws) = draws.filter(|_| (3..5).contains(&board_len)) {
+        result["draws"] = draws.iter().map(format_draws).collect();
     }
+    let evaluation = Evaluation { result, hands };
+    // how far sampling got before a deadline or a disconnect depends on the load at the time
+    let repeatable = matches!(
+        stop_reason,
+        None | Some(StopReason::MaxSamples | StopReason::TargetStandardError)
+    );
+    if repeatable {
+        cache.lock().unwrap().insert(key, evaluation.clone());
+    }
+    Body::from_json(&in_suits(evaluation, permutation.inverse()))
+}
+
+/// Maps the outcomes' cards from the canonical suits back to those of a query.
+fn in_suits(evaluation: Evaluation, permutation: SuitPermutation) -> Value {
+    let Evaluation { mut result, hands } = evaluation;
+    if let Some(outcomes) = result.get_mut("outcomes").and_then(Value::as_array_mut) {
+        for (outcome, cards) in zip(outcomes, hands) {
+            outcome["cards"] = json!(cards.map(|card| permutation.apply(card).to_string()));
+        }
+    }
+    result
+}
+
+async fn next_card(mut req: Request<State>) -> tide::Result<Body> {
+    let input: Input = req.body_json().await?;
+    let rng = RngAdapter(Rng::with_seed(1));
+    let rule = input.stopping_rule();
+    let game = input.game();
+    let NextCards { equity, cards } = cancellable(move |cancelled| {
+        game.next_cards_observed(rng, rule, |_, _, _| until(cancelled))
+    })
+    .await?;
+    let group = |effect| {
+        cards
+            .iter()
+            .filter(|next| next.effect == effect)
+            .map(|next| next.card.to_string())
+            .collect::<Vec<_>>()
+    };
+    let result = json!({
+        "equity": equity,
+        "cards": cards.iter().map(|next| json!({
+            "card": next.card.to_string(),
+            "equity": next.equity,
+        })).collect::<Vec<_>>(),
+        "improves_hero": group(Effect::ImprovesHero),
+        "improves_villain": group(Effect::ImprovesVillain),
+        "neutral": group(Effect::Neutral),
+    });
+    Body::from_json(&result)
+}
+
+async fn nuts(mut req: Request<State>) -> tide::Result<Body> {
+    let input: Input = req.body_json().await?;
+    let n = input.top.unwrap_or(10).min(1000);
+    let game = input.game();
+    let Nuts { top, strength } = cancellable(move |cancelled| {
+        game.nuts_cancellable(n, || cancelled.load(Ordering::Relaxed))
+    })
+    .await?;
+    let result = json!({
+        "top": top.into_iter().map(|holding| json!({
+            "rank": holding.rank,
+            "hole_cards": holding.hole_cards.iter().map(Card::to_string).collect::<Vec<_>>(),
+            "hand_type": holding.hand.hand_type.to_string(),
+            "cards": holding.hand.cards.map(|card| card.to_string()),
+        })).collect::<Vec<_>>(),
+        "strength": strength,
+    });
+    Body::from_json(&result)
+}
+
+async fn cache_stats(req: Request<State>) -> tide::Result<Body> {
+    let CacheStats {
+        hits,
+        misses,
+        entries,
+    } = req.state().cache.lock().unwrap().stats();
+    Body::from_json(&json!({
+        "hits": hits,
+        "misses": misses,
+        "entries": entries,
+    }))
 }
 
-fn format_odds(odds: Odds, cards_remaining: usize, n_players: usize) -> tide::Result<Body> {
-    let odds = odds
-        .merge_unknown_players(n_players)
+async fn play(game: Game, rng: RngAdapter, rule: StoppingRule) -> Result<GameOutcome, GameError> {
+    cancellable(move |cancelled| game.play_observed(rng, rule, |_, _, _| until(cancelled))).await
+}
+
+/// Runs `work` on a blocking thread, raising its flag if this future is dropped first.
+async fn cancellable<T: Send + 'static>(work: impl FnOnce(&AtomicBool) -> T + Send + 'static) -> T {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(cancelled.clone());
+    task::spawn_blocking(move || work(&cancelled)).await
+}
+
+fn until(cancelled: &AtomicBool) -> ControlFlow<()> {
+    match cancelled.load(Ordering::Relaxed) {
+        true => ControlFlow::Break(()),
+        false => ControlFlow::Continue(()),
+    }
+}
+
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+fn format_odds(
+    odds: Odds,
+    n_players: usize,
+    variant: Variant,
+    method: Method,
+    streets: Option<&Vec<&str>>,
+) -> Vec<Value> {
+    odds.merge_unknown_players(n_players)
         .into_iter()
         .map(|o| {
             let distribution = o
                 .distribution()
-                .map(|(hand_type, value)| (hand_type.to_string(), value))
+                .map(|(hand_type, value, _)| (hand_type.to_string(), value))
                 .collect::<HashMap<_, _>>();
 
             let (label, value) = match o.who {
@@ -83,31 +448,135 @@ fn format_odds(odds: Odds, cards_remaining: usize, n_players: usize) -> tide::Re
             let win = o.win_percent();
             let loss = o.loss_percent();
             let tie = o.tie_percent();
-            json!({
+            let equity = o.equity_percent();
+            let mut odds = json!({
                 label: value,
                 "win": win,
                 "loss": loss,
                 "tie": tie,
+                "equity": equity,
                 "distribution": distribution
-            })
+            });
+            if variant.is_hi_lo() {
+                odds["scoop"] = json!(o.scoop_percent());
+                odds["low"] = json!(o.low_win_percent());
+                odds["split"] = json!(o.split_percent());
+                odds["quartered"] = json!(o.quartered_percent());
+            }
+            if let Some(streets) = streets.filter(|_| !variant.streets().is_empty()) {
+                let equities = o.street_equity_percent().chain([equity]);
+                odds["streets"] = zip(streets, equities)
+                    .map(|(street, equity)| json!({"street": street, "equity": equity}))
+                    .collect();
+            }
+            if method == Method::MonteCarlo {
+                let distribution = o
+                    .distribution()
+                    .map(|(hand_type, value, error)| {
+                        (hand_type.to_string(), format_error(value, error))
+                    })
+                    .collect::<HashMap<_, _>>();
+                odds["error"] = json!({
+                    "win": format_error(win, o.win_standard_error()),
+                    "loss": format_error(loss, o.loss_standard_error()),
+                    "tie": format_error(tie, o.tie_standard_error()),
+                    "equity": format_error(equity, o.equity_standard_error()),
+                    "distribution": distribution,
+                });
+            }
+            odds
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
 
-    Body::from_json(&json!({
-        "cards_remaining": cards_remaining,
-        "odds": odds,
-    }))
+fn format_ev(ev: Ev) -> Value {
+    json!({
+        "call": ev.call,
+        "required_equity": ev.required_equity,
+        "bluff_fold_frequency": ev.bluff_fold_frequency,
+    })
 }
 
-fn format_outcomes(outcomes: Vec<HandOutcome>, cards_remaining: usize) -> tide::Result<Body> {
-    Body::from_json(&json!({
-        "cards_remaining": cards_remaining,
-        "outcomes": outcomes.into_iter().map(|outcome| json!({
-            "outcome": format!("{:?}", outcome.outcome),
-            "hand_type": format!("{}", outcome.hand.hand_type),
-            "cards": outcome.hand.cards.map(|card| card.to_string()),
-        })).collect::<Vec<_>>()
-    }))
+fn format_pots(pots: Pots) -> Value {
+    let Pots {
+        pots,
+        equity,
+        chip_ev,
+    } = pots;
+    json!({
+        "pots": pots.into_iter().enumerate().map(|(k, pot)| json!({
+            "amount": pot.amount,
+            "equity": pot.eligible.iter().map(|i| json!({
+                "player": i + 1,
+                "equity": equity[*i][k],
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+        "chip_ev": chip_ev,
+    })
+}
+
+fn format_texture(texture: Texture) -> Value {
+    json!({
+        "pairing": texture.pairing.to_string(),
+        "suits": texture.suits.to_string(),
+        "connectedness": texture.connectedness.to_string(),
+        "high_card": texture.high_card.to_string(),
+        "straights": texture.straights,
+        "flushes": texture.flushes,
+    })
+}
+
+fn format_draws(draws: &DrawDistribution) -> Value {
+    draws
+        .percentages()
+        .map(|(draw, percent)| (draw.to_string(), json!(percent)))
+        .collect()
+}
+
+fn format_error(percent: f64, standard_error: f64) -> Value {
+    let (low, high) = confidence_interval(percent, standard_error);
+    json!({
+        "standard_error": standard_error,
+        "confidence_interval": [low, high],
+    })
+}
+
+fn format_method(method: Method) -> &'static str {
+    match method {
+        Method::Exhaustive => "exhaustive",
+        Method::MonteCarlo => "monte_carlo",
+        Method::Lookup => "lookup",
+    }
+}
+
+fn format_stop_reason(reason: StopReason) -> &'static str {
+    match reason {
+        StopReason::MaxSamples => "max_samples",
+        StopReason::TargetStandardError => "target_error",
+        StopReason::Deadline => "time_limit",
+        StopReason::Cancelled => "cancelled",
+    }
+}
+
+fn format_outcomes(outcomes: Vec<HandOutcome>) -> Vec<Value> {
+    outcomes
+        .into_iter()
+        .map(|outcome| {
+            let mut result = json!({
+                "outcome": format!("{:?}", outcome.outcome),
+                "hand_type": format!("{}", outcome.hand.hand_type),
+                "cards": outcome.hand.cards.map(|card| card.to_string()),
+            });
+            if let Some((low_outcome, low)) = outcome.low {
+                result["low"] = json!({
+                    "outcome": format!("{:?}", low_outcome),
+                    "ranks": low.map(|low| low.ranks().map(|rank| rank.to_string()).collect::<Vec<_>>()),
+                });
+                result["share"] = json!(outcome.share);
+            }
+            result
+        })
+        .collect()
 }
 
 struct RngAdapter(Rng);
@@ -117,3 +586,80 @@ impl odd_engine::Rng<usize> for RngAdapter {
         self.0.usize(range)
     }
 }
+
+impl odd_engine::SplitRng for RngAdapter {
+    fn stream(&self, index: u64) -> Self {
+        RngAdapter(Rng::with_seed(odd_engine::split_seed(
+            self.0.get_seed(),
+            index,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tide::http::{Method, Url};
+    use tide::StatusCode;
+
+    async fn post(app: &tide::Server<State>, input: Value) -> tide::http::Response {
+        let url = Url::parse("http://localhost/evaluate").unwrap();
+        let mut req = tide::http::Request::new(Method::Post, url);
+        req.set_body(Body::from_json(&input).unwrap());
+        app.respond(req).await.unwrap()
+    }
+
+    async fn evaluate_json(app: &tide::Server<State>, input: Value) -> Value {
+        post(app, input).await.body_json().await.unwrap()
+    }
+
+    fn test_app() -> tide::Server<State> {
+        app(State {
+            preflop_table: None,
+            cache: Arc::new(Mutex::new(Cache::new(10, Duration::from_secs(60)))),
+        })
+    }
+
+    #[async_std::test]
+    async fn test_cached_outcomes_in_query_suits() {
+        let app = test_app();
+        let first = evaluate_json(
+            &app,
+            json!({"players": [["As", "Ks"], ["Qh", "Qd"]], "board": ["Ah", "Kd", "2c", "7s", "9h"]}),
+        )
+        .await;
+        // the same spot with spades and hearts swapped
+        let second = evaluate_json(
+            &app,
+            json!({"players": [["Ah", "Kh"], ["Qs", "Qd"]], "board": ["As", "Kd", "2c", "7h", "9s"]}),
+        )
+        .await;
+        assert_eq!(1, app.state().cache.lock().unwrap().stats().hits);
+        let cards = |result: &Value| result["outcomes"][0]["cards"].clone();
+        let in_suits =
+            |cards: [&str; 5]| json!(cards.map(|card| card.parse::<Card>().unwrap().to_string()));
+        assert_eq!(in_suits(["As", "Ah", "Ks", "Kd", "9h"]), cards(&first));
+        assert_eq!(in_suits(["Ah", "As", "Kh", "Kd", "9s"]), cards(&second));
+    }
+
+    #[async_std::test]
+    async fn test_rejects_zero_iterations() {
+        let input = json!({"players": [["As", "Ks"], ["Qh", "Qd"]], "board": [], "iterations": 0});
+        let res = post(&test_app(), input).await;
+        assert_eq!(StatusCode::UnprocessableEntity, res.status());
+    }
+
+    #[async_std::test]
+    async fn test_does_not_cache_results_cut_short() {
+        let app = test_app();
+        let input = json!({
+            "players": [["As", "Ks"], ["Qh", "Qd"]],
+            "board": [],
+            "opponents": 1,
+            "time_limit": 0,
+        });
+        let result = evaluate_json(&app, input).await;
+        assert_eq!(json!("time_limit"), result["stop_reason"]);
+        assert_eq!(0, app.state().cache.lock().unwrap().stats().entries);
+    }
+}